@@ -1,31 +1,69 @@
+// Every algorithm names its empty step `Action::NoAction`.
+#![allow(clippy::enum_variant_names)]
+
+pub mod bentley_ottmann;
+pub mod calipers_common;
+pub mod calipers_diameter;
+pub mod calipers_rectangle;
+pub mod calipers_width;
+pub mod chan;
 pub mod closest_pair_dnc;
 pub mod closest_pair_sl;
 pub mod convex_hull_dnc;
+pub mod delaunay_bw;
+pub mod ear_clipping;
 pub mod graham;
 pub mod graham_andrew;
 pub mod graham_common;
+pub mod half_plane;
+pub mod incremental_hull;
+pub mod jarvis;
+pub mod kd_tree;
+pub mod kirkpatrick_seidel;
+pub mod monotone_partition;
+pub mod monotone_triangulation;
+pub mod quadtree;
+pub mod quickhull;
+pub mod range_tree;
 pub mod shamos_hoey;
+pub mod trapezoidal_map;
+pub mod voronoi_fortune;
+pub mod welzl;
 
-use crate::common::Point;
-use crate::draw_context::DrawContext;
+use crate::canvas::Canvas;
 
 pub trait Algo {
     type State: Clone + std::fmt::Debug;
     type Action: Clone + std::fmt::Debug;
+    // Points for most of the algorithms
+    type Input: Clone + std::fmt::Debug;
 
-    fn first_state(points: Vec<Point>) -> Self::State;
+    fn first_state(input: Self::Input) -> Self::State;
     fn next_state(state: Self::State) -> (Self::State, Self::Action);
     fn is_final(state: &Self::State) -> bool;
 
-    fn draw_state(dc: &mut DrawContext, state: &Self::State);
-    fn draw_action(dc: &mut DrawContext, action: &Self::Action);
+    fn draw_state(dc: &mut dyn Canvas, state: &Self::State);
+    fn draw_action(dc: &mut dyn Canvas, action: &Self::Action);
+}
+
+// Input data which can be edited and parameters which stay the same, like a query point
+#[derive(Clone, Debug)]
+pub struct WithParams<T, P> {
+    pub data: T,
+    pub params: P,
+}
+
+impl<T, P> WithParams<T, P> {
+    pub fn new(data: T, params: P) -> Self {
+        Self { data, params }
+    }
 }
 
-pub fn all_states<TAlgo>(points: Vec<Point>) -> (Vec<TAlgo::State>, Vec<TAlgo::Action>)
+pub fn all_states<TAlgo>(input: TAlgo::Input) -> (Vec<TAlgo::State>, Vec<TAlgo::Action>)
 where
     TAlgo: Algo,
 {
-    let mut states = vec![TAlgo::first_state(points)];
+    let mut states = vec![TAlgo::first_state(input)];
     let mut actions = Vec::new();
     while !TAlgo::is_final(states.last().unwrap()) {
         let (next, action) = TAlgo::next_state(states.last().unwrap().clone());
@@ -34,3 +72,18 @@ where
     }
     (states, actions)
 }
+
+// Even indexes show a state, odd ones show the state with the next action on top
+pub fn draw_step<TAlgo>(
+    dc: &mut dyn Canvas,
+    states: &[TAlgo::State],
+    actions: &[TAlgo::Action],
+    index: usize,
+) where
+    TAlgo: Algo,
+{
+    TAlgo::draw_state(dc, &states[index / 2]);
+    if index % 2 != 0 {
+        TAlgo::draw_action(dc, &actions[index / 2]);
+    }
+}
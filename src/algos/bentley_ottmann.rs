@@ -0,0 +1,368 @@
+use crate::algos::shamos_hoey::{make_segments, neighbors};
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+// Points closer than this are the same event, and a segment this close to a point goes through it
+const TOLERANCE: f32 = 1e-4;
+
+fn same_point(a: &Point, b: &Point) -> bool {
+    (a.x - b.x).abs() < TOLERANCE && (a.y - b.y).abs() < TOLERANCE
+}
+
+// Points after `p` in the sweep, those at `p` itself are handled by its event
+fn is_after(q: &Point, p: &Point) -> bool {
+    cmp_by_xy(q, p) == Ordering::Greater && !same_point(q, p)
+}
+
+fn contains(s: &Segment, p: &Point) -> bool {
+    let d = s.b - s.a;
+    let t = ((*p - s.a).dot(d) / d.square_length()).clamp(0., 1.);
+    (*p - (s.a + d * t)).length() < TOLERANCE
+}
+
+fn is_vertical(s: &Segment) -> bool {
+    s.a.x == s.b.x
+}
+
+// Every endpoint and intersection point is a single event, whatever meets there
+#[derive(Clone, Debug)]
+struct Event {
+    p: Point,
+    starts: Vec<usize>,
+    cross: bool,
+}
+
+// Segments through an event are cut at it, so they are ordered by slope in `cur_segments`.
+// Vertical segments are kept aside, they only meet the others at a single x
+#[derive(Clone, Debug)]
+pub struct State {
+    segments: Vec<Segment>,
+    current: Vec<Segment>,
+    cur_segments: BTreeSet<Segment>,
+    verticals: Vec<usize>,
+    // from the last one
+    events: Vec<Event>,
+    intersections: Vec<Point>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    // the event point, the segments through it and the new intersection events
+    Event((Point, Vec<Segment>, Vec<Point>)),
+}
+
+fn cross(a: &Point, b: &Point) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// Intersection of non-parallel segments which are known to intersect
+fn intersection_point(first: &Segment, second: &Segment) -> Option<Point> {
+    let d1 = first.b - first.a;
+    let d2 = second.b - second.a;
+    let denom = cross(&d1.to_point(), &d2.to_point());
+    if eps_equal(denom, 0.) {
+        return None;
+    }
+    let t = cross(&(second.a - first.a).to_point(), &d2.to_point()) / denom;
+    Some(first.a + d1 * t)
+}
+
+impl State {
+    fn id(&self, seg: &Segment) -> usize {
+        self.current
+            .iter()
+            .position(|s| s.a == seg.a && s.b == seg.b)
+            .unwrap()
+    }
+
+    // Events are sorted from left to right, points close to an event are merged into it
+    fn add_event(&mut self, event: Event) {
+        let pos = match self.events.binary_search_by(|e| cmp_by_xy(&event.p, &e.p)) {
+            Ok(i) | Err(i) => i,
+        };
+        let near = |i: &usize| (self.events[*i].p.x - event.p.x).abs() < TOLERANCE;
+        let close = (0..pos)
+            .rev()
+            .take_while(near)
+            .chain((pos..self.events.len()).take_while(near))
+            .find(|i| same_point(&self.events[*i].p, &event.p));
+        match close {
+            Some(i) => {
+                self.events[i].starts.extend(event.starts);
+                self.events[i].cross |= event.cross;
+            }
+            None => self.events.insert(pos, event),
+        }
+    }
+
+    // Intersections to the left of `p` are already found
+    fn schedule(
+        &mut self,
+        first: Option<Segment>,
+        second: Option<Segment>,
+        p: &Point,
+    ) -> Option<Point> {
+        let (first, second) = (first?, second?);
+        if !intersect_seg(&first, &second) {
+            return None;
+        }
+        // overlapping segments meet again only at an endpoint
+        let q = intersection_point(&first, &second)?;
+        if !is_after(&q, p) {
+            return None;
+        }
+        self.add_event(Event {
+            p: q,
+            starts: Vec::new(),
+            cross: true,
+        });
+        Some(q)
+    }
+
+    // Segments of the status through `p`, they are next to each other
+    fn through(&self, p: &Point) -> Vec<Segment> {
+        let probe = Segment::new(*p, *p);
+        let below = self.cur_segments.range(..probe).rev();
+        let above = self.cur_segments.range(probe..);
+        below
+            .take_while(|s| contains(s, p))
+            .chain(above.take_while(|s| contains(s, p)))
+            .copied()
+            .collect()
+    }
+
+    // Crossings of the status with a vertical segment starting at `p`
+    fn schedule_vertical(&mut self, v: &Segment, p: &Point) -> Vec<Point> {
+        let x = v.a.x;
+        let crossings: Vec<Point> = self
+            .cur_segments
+            .range(Segment::new(*p, *p)..)
+            .map(|s| Point::new(x, s.get_y(x)))
+            .take_while(|q| q.y < v.b.y + TOLERANCE)
+            .filter(|q| is_after(q, p))
+            .collect();
+        for q in &crossings {
+            self.add_event(Event {
+                p: *q,
+                starts: Vec::new(),
+                cross: true,
+            });
+        }
+        crossings
+    }
+
+    fn remove(&mut self, seg: &Segment) {
+        // rounding may leave the order a bit off, then the segment is looked up one by one
+        if !self.cur_segments.remove(seg) {
+            self.cur_segments.retain(|s| s.a != seg.a || s.b != seg.b);
+        }
+    }
+}
+
+pub struct BentleyOttmann;
+
+impl Algo for BentleyOttmann {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let segments = make_segments(points);
+        let mut state = State {
+            current: segments.clone(),
+            segments,
+            cur_segments: BTreeSet::new(),
+            verticals: Vec::new(),
+            events: Vec::new(),
+            intersections: Vec::new(),
+        };
+        for (id, seg) in state.segments.clone().iter().enumerate() {
+            state.add_event(Event {
+                p: seg.a,
+                starts: vec![id],
+                cross: false,
+            });
+            state.add_event(Event {
+                p: seg.b,
+                starts: Vec::new(),
+                cross: false,
+            });
+        }
+        state
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if Self::is_final(&state) {
+            return (state, Action::NoAction);
+        }
+
+        let Event { p, starts, .. } = state.events.pop().unwrap();
+        let through = state.through(&p);
+        let mut ids: Vec<usize> = through.iter().map(|s| state.id(s)).collect();
+        ids.extend(
+            state
+                .verticals
+                .iter()
+                .filter(|id| contains(&state.current[**id], &p)),
+        );
+        for seg in &through {
+            state.remove(seg);
+        }
+        let State {
+            current, verticals, ..
+        } = &mut state;
+        verticals.retain(|id| !same_point(&current[*id].b, &p));
+
+        // segments going on to the right of `p`
+        let mut inserted = Vec::new();
+        for seg in &through {
+            if !same_point(&seg.b, &p) {
+                let id = state.id(seg);
+                state.current[id] = Segment::new(p, seg.b);
+                inserted.push(state.current[id]);
+            }
+        }
+        let mut found = Vec::new();
+        for id in &starts {
+            let seg = state.current[*id];
+            if is_vertical(&seg) {
+                state.verticals.push(*id);
+                found.extend(state.schedule_vertical(&seg, &p));
+            } else {
+                inserted.push(seg);
+            }
+        }
+        ids.extend(starts);
+        if ids.len() > 1 {
+            state.intersections.push(p);
+        }
+
+        for seg in &inserted {
+            state.cur_segments.insert(*seg);
+        }
+        match (inserted.iter().min(), inserted.iter().max()) {
+            (Some(lowest), Some(highest)) => {
+                let below = neighbors(&state.cur_segments, lowest).0.copied();
+                let above = neighbors(&state.cur_segments, highest).1.copied();
+                found.extend(state.schedule(below, Some(*lowest), &p));
+                found.extend(state.schedule(Some(*highest), above, &p));
+            }
+            _ => {
+                let probe = Segment::new(p, p);
+                let (below, above) = neighbors(&state.cur_segments, &probe);
+                let (below, above) = (below.copied(), above.copied());
+                found.extend(state.schedule(below, above, &p));
+            }
+        }
+        let segments = ids.iter().map(|id| state.segments[*id]).collect();
+        (state, Action::Event((p, segments, found)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.events.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for seg in &state.segments {
+            dc.draw_line(&seg.a, &seg.b, WHITE_COLOR);
+        }
+        let verticals = state.verticals.iter().map(|id| &state.current[*id]);
+        for seg in state.cur_segments.iter().chain(verticals) {
+            dc.draw_line(&seg.a, &seg.b, BLUE_COLOR);
+        }
+        for event in state.events.iter().filter(|e| e.cross) {
+            dc.draw_point(&event.p, YELLOW_COLOR);
+        }
+        for p in &state.intersections {
+            dc.draw_point(p, GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Event((p, segments, found)) => {
+                dc.draw_vertical_line(p.x, BLUE_COLOR);
+                for seg in segments {
+                    dc.draw_line(&seg.a, &seg.b, YELLOW_COLOR);
+                }
+                let color = if segments.len() > 1 {
+                    GREEN_COLOR
+                } else {
+                    YELLOW_COLOR
+                };
+                dc.draw_point(p, color);
+                for p in found {
+                    dc.draw_point(p, RED_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Grid, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Distinct points where two or more segments meet, overlaps give the ends of the common part
+    fn brute_force(segments: &[Segment]) -> Vec<Point> {
+        let mut res: Vec<Point> = Vec::new();
+        for (i, first) in segments.iter().enumerate() {
+            for second in &segments[i + 1..] {
+                let ends = [first.a, first.b, second.a, second.b];
+                let crossing = intersection_point(first, second);
+                let points: Vec<Point> = match crossing.filter(|_| intersect_seg(first, second)) {
+                    Some(p) => vec![p],
+                    // an end close to the other segment touches it
+                    None => ends
+                        .iter()
+                        .filter(|p| contains(first, p) && contains(second, p))
+                        .copied()
+                        .collect(),
+                };
+                for p in points {
+                    if res.iter().all(|r| !same_point(r, &p)) {
+                        res.push(p);
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    fn check(points: Vec<Point>, seed: u64) {
+        let expected = brute_force(&make_segments(points.clone()));
+        let (states, _) = all_states::<BentleyOttmann>(points);
+        let reported = &states.last().unwrap().intersections;
+        assert_eq!(expected.len(), reported.len(), "seed {}", seed);
+        for p in &expected {
+            assert!(
+                reported.iter().any(|r| same_point(r, p)),
+                "seed {}: {:?} is missing",
+                seed,
+                p
+            );
+        }
+    }
+
+    #[test]
+    fn reports_every_intersection() {
+        for seed in 0..50 {
+            check(Uniform.generate(60, &mut StdRng::seed_from_u64(seed)), seed);
+        }
+        // several segments through a point, ends on vertical segments and overlaps
+        for seed in 0..200 {
+            for n in &[10, 30] {
+                check(Grid.generate(*n, &mut StdRng::seed_from_u64(seed)), seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::algos::graham_andrew::convex_hull;
+use crate::canvas::*;
+use crate::common::*;
+use euclid::Vector2D;
+use raqote::SolidSource;
+
+pub type Vector = Vector2D<f32, f32>;
+
+// Support lines of a single edge: one on the edge, the other parallel through `far`
+#[derive(Copy, Clone, Debug)]
+pub struct Support {
+    pub edge: Pair,
+    pub far: Point,
+}
+
+// One support line lies on the hull edge from `edge` to `edge + 1`,
+// the other vertices are the extremes in the directions along and across it.
+// Indexes only grow, they are taken modulo the hull size
+#[derive(Clone, Debug)]
+pub struct Calipers {
+    pub hull: Vec<Point>,
+    pub edge: usize,
+    // farthest from the edge line, the parallel support line goes through it
+    pub far: usize,
+    // largest and smallest projection onto the edge direction
+    pub front: usize,
+    pub back: usize,
+}
+
+impl Calipers {
+    pub fn new(points: Vec<Point>) -> Self {
+        let mut calipers = Self {
+            hull: convex_hull(points),
+            edge: 0,
+            far: 0,
+            front: 0,
+            back: 0,
+        };
+        calipers.settle();
+        calipers
+    }
+
+    pub fn at(&self, i: usize) -> Point {
+        self.hull[i % self.hull.len()]
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.hull.len() < 2 || self.edge == self.hull.len()
+    }
+
+    pub fn edge_pair(&self) -> Pair {
+        Pair::new(self.at(self.edge), self.at(self.edge + 1))
+    }
+
+    pub fn support(&self) -> Support {
+        Support {
+            edge: self.edge_pair(),
+            far: self.at(self.far),
+        }
+    }
+
+    // Unit vector along the current edge
+    pub fn direction(&self) -> Vector {
+        let e = self.edge_pair();
+        (e.b - e.a).normalize()
+    }
+
+    // Unit vector across the current edge, pointing inside of the hull
+    pub fn normal(&self) -> Vector {
+        let d = self.direction();
+        Vector::new(-d.y, d.x)
+    }
+
+    pub fn width(&self) -> f32 {
+        (self.at(self.far) - self.at(self.edge)).dot(self.normal())
+    }
+
+    // Smallest rectangle with a side on the current edge, counterclockwise
+    pub fn rectangle(&self) -> [Point; 4] {
+        let (a, d, n) = (self.at(self.edge), self.direction(), self.normal());
+        let front = (self.at(self.front) - a).dot(d);
+        let back = (self.at(self.back) - a).dot(d);
+        let w = self.width();
+        [
+            a + d * back,
+            a + d * front,
+            a + d * front + n * w,
+            a + d * back + n * w,
+        ]
+    }
+
+    // Moves to the next edge and rotates the other calipers along
+    pub fn rotate(&mut self) {
+        self.edge += 1;
+        if !self.is_done() {
+            self.settle();
+        }
+    }
+
+    fn settle(&mut self) {
+        if self.hull.len() < 2 {
+            return;
+        }
+        let (a, d, n) = (self.at(self.edge), self.direction(), self.normal());
+        let along = |p: Point| (p - a).dot(d);
+        let across = |p: Point| (p - a).dot(n);
+        // the extremes follow the edge in this order around a counterclockwise hull
+        self.front = self.advance(self.front.max(self.edge + 1), along);
+        self.far = self.advance(self.far.max(self.front), across);
+        self.back = self.advance(self.back.max(self.far), |p| -along(p));
+    }
+
+    fn advance<F>(&self, mut i: usize, value: F) -> usize
+    where
+        F: Fn(Point) -> f32,
+    {
+        let limit = self.edge + self.hull.len();
+        while i < limit && value(self.at(i + 1)) > value(self.at(i)) {
+            i += 1;
+        }
+        i
+    }
+}
+
+// Line through `p` along `d` clipped to the visible area
+pub fn draw_support_line(dc: &mut dyn Canvas, p: &Point, d: &Vector, color: SolidSource) {
+    dc.draw_infinite_line(&Line::new(*p, *p + *d), color);
+}
+
+pub fn draw_hull(dc: &mut dyn Canvas, points: &[Point], calipers: &Calipers) {
+    for point in points {
+        dc.draw_point(point, WHITE_COLOR);
+    }
+    dc.draw_polygon(&calipers.hull, BLUE_COLOR);
+}
+
+pub fn draw_support(dc: &mut dyn Canvas, support: &Support, color: SolidSource) {
+    let edge = &support.edge;
+    let d = (edge.b - edge.a).normalize();
+    draw_support_line(dc, &edge.a, &d, color);
+    draw_support_line(dc, &support.far, &d, color);
+    dc.draw_line(&edge.a, &edge.b, YELLOW_COLOR);
+    dc.draw_point(&support.far, YELLOW_COLOR);
+}
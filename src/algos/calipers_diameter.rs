@@ -0,0 +1,108 @@
+use crate::algos::calipers_common::{draw_hull, draw_support, Calipers, Support};
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    calipers: Calipers,
+    best: Option<Pair>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Antipodal((Support, Vec<Pair>)),
+}
+
+pub struct CalipersDiameter;
+
+impl Algo for CalipersDiameter {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        State {
+            calipers: Calipers::new(points.clone()),
+            points,
+            best: None,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.calipers.is_done() {
+            return (state, Action::NoAction);
+        }
+        let c = &state.calipers;
+        let edge = c.edge_pair();
+        let mut antipodal = vec![c.at(c.far)];
+        // the next vertex is antipodal as well if its edge is parallel to the current one
+        let next = c.at(c.far + 1);
+        if eps_equal((next - edge.a).dot(c.normal()), c.width()) {
+            antipodal.push(next);
+        }
+        let pairs: Vec<Pair> = antipodal
+            .iter()
+            .flat_map(|p| vec![Pair::new(edge.a, *p), Pair::new(edge.b, *p)])
+            .collect();
+        for pair in &pairs {
+            if state
+                .best
+                .map_or(true, |best| pair.square_len() > best.square_len())
+            {
+                state.best = Some(*pair);
+            }
+        }
+        let support = c.support();
+        state.calipers.rotate();
+        (state, Action::Antipodal((support, pairs)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.calipers.is_done()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        draw_hull(dc, &state.points, &state.calipers);
+        if let Some(best) = state.best {
+            dc.draw_path(&[best.a, best.b], GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Antipodal((support, pairs)) => {
+                draw_support(dc, support, WHITE_COLOR);
+                for pair in pairs {
+                    dc.draw_line(&pair.a, &pair.b, RED_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn finds_the_farthest_pair() {
+        for seed in 0..20 {
+            let points = Uniform.generate(40, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<CalipersDiameter>(points.clone());
+            let best = states.last().unwrap().best.unwrap();
+            let expected = points
+                .iter()
+                .flat_map(|a| points.iter().map(move |b| Pair::new(*a, *b).square_len()))
+                .fold(0., f32::max);
+            assert!(eps_equal(best.square_len(), expected), "seed {}", seed);
+        }
+    }
+}
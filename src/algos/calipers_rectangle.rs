@@ -0,0 +1,111 @@
+use crate::algos::calipers_common::{draw_hull, draw_support, Calipers, Support};
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+pub type Rectangle = [Point; 4];
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    calipers: Calipers,
+    best: Option<(Rectangle, f32)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Rectangle((Support, Rectangle)),
+}
+
+pub struct CalipersRectangle;
+
+impl Algo for CalipersRectangle {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        State {
+            calipers: Calipers::new(points.clone()),
+            points,
+            best: None,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.calipers.is_done() {
+            return (state, Action::NoAction);
+        }
+        // the smallest rectangle has one of its sides on a hull edge
+        let support = state.calipers.support();
+        let rectangle = state.calipers.rectangle();
+        let area = signed_area(&rectangle);
+        if state.best.map_or(true, |(_, best)| area < best) {
+            state.best = Some((rectangle, area));
+        }
+        state.calipers.rotate();
+        (state, Action::Rectangle((support, rectangle)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.calipers.is_done()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        draw_hull(dc, &state.points, &state.calipers);
+        if let Some((best, _)) = &state.best {
+            dc.draw_polyline(&[best[0], best[1], best[2], best[3], best[0]], GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Rectangle((support, r)) => {
+                draw_support(dc, support, WHITE_COLOR);
+                dc.draw_polyline(&[r[0], r[1], r[2], r[3], r[0]], RED_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn encloses_points_with_the_smallest_rectangle() {
+        for seed in 0..20 {
+            let points = Uniform.generate(40, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<CalipersRectangle>(points.clone());
+            let (rectangle, area) = states.last().unwrap().best.unwrap();
+            for p in &points {
+                assert!(
+                    (0..4).all(|i| rotation(&rectangle[i], &rectangle[(i + 1) % 4], p) >= -1e-3),
+                    "seed {}",
+                    seed
+                );
+            }
+
+            // bounding boxes of the points rotated by many angles are never smaller
+            for step in 0..360 {
+                let angle = std::f32::consts::PI * step as f32 / 360.;
+                let (sin, cos) = angle.sin_cos();
+                let (xs, ys): (Vec<f32>, Vec<f32>) = points
+                    .iter()
+                    .map(|p| (p.x * cos - p.y * sin, p.x * sin + p.y * cos))
+                    .unzip();
+                let span = |v: &[f32]| {
+                    v.iter().copied().fold(f32::MIN, f32::max)
+                        - v.iter().copied().fold(f32::MAX, f32::min)
+                };
+                assert!(span(&xs) * span(&ys) >= area - 1e-3, "seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::algos::calipers_common::{draw_hull, draw_support, Calipers, Support};
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    calipers: Calipers,
+    best: Option<(Support, f32)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Width((Support, f32)),
+}
+
+pub struct CalipersWidth;
+
+impl Algo for CalipersWidth {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        State {
+            calipers: Calipers::new(points.clone()),
+            points,
+            best: None,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.calipers.is_done() {
+            return (state, Action::NoAction);
+        }
+        // the narrowest strip has one of its sides on a hull edge
+        let support = state.calipers.support();
+        let width = state.calipers.width();
+        if state.best.map_or(true, |(_, best)| width < best) {
+            state.best = Some((support, width));
+        }
+        state.calipers.rotate();
+        (state, Action::Width((support, width)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.calipers.is_done()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        draw_hull(dc, &state.points, &state.calipers);
+        if let Some((best, _)) = &state.best {
+            draw_support(dc, best, GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Width((support, _)) => draw_support(dc, support, WHITE_COLOR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn finds_the_narrowest_strip() {
+        for seed in 0..20 {
+            let points = Uniform.generate(40, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<CalipersWidth>(points.clone());
+            let (_, width) = states.last().unwrap().best.unwrap();
+
+            // the narrowest strip along the line through any two of the points
+            let mut expected = f32::INFINITY;
+            for a in &points {
+                for b in &points {
+                    if a == b {
+                        continue;
+                    }
+                    let len = (*b - *a).length();
+                    let (lo, hi) = points
+                        .iter()
+                        .map(|p| rotation(a, b, p) / len)
+                        .fold((0f32, 0f32), |(lo, hi), d| (lo.min(d), hi.max(d)));
+                    if lo >= -1e-4 || hi <= 1e-4 {
+                        expected = expected.min(hi - lo);
+                    }
+                }
+            }
+            assert!((width - expected).abs() < 1e-3, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::algos::graham_common::{draw_graham_action, draw_progress, sort_by_angle, step};
+use crate::algos::jarvis::is_better;
+use crate::algos::{graham_common, Algo};
+use crate::canvas::*;
+use crate::common::*;
+use raqote::SolidSource;
+
+const FIRST_GUESS: usize = 4;
+
+#[derive(Copy, Clone, Debug)]
+enum Phase {
+    Group,
+    MiniHulls,
+    Wrap,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    m: usize,
+    phase: Phase,
+    groups: Vec<Vec<Point>>,
+    left: Vec<Point>,
+    building: Vec<Point>,
+    mini_hulls: Vec<Vec<Point>>,
+    hull: Vec<Point>,
+    best: Option<Point>,
+    next_hull: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Group((usize, Vec<Vec<Point>>)),
+    MiniHull(graham_common::Action),
+    Tangent((Point, Vec<Point>, Point, Point, bool)),
+    Accept(Pair),
+    FailedGuess((Vec<Point>, usize)),
+}
+
+// Binary search for the vertex `q` of the counterclockwise convex `hull` such that
+// the whole hull is to the left of `p -> q`, `p` should be outside of the hull.
+// Based on Dan Sunday's tangent_PointPolyC
+fn tangent(hull: &[Point], p: &Point) -> usize {
+    let n = hull.len();
+    let v = |i: usize| &hull[i % n];
+    // `a` is above `b` if `b` is to the left of `p -> a`
+    let above = |a: &Point, b: &Point| rotation(p, a, b) > 0.;
+    let below = |a: &Point, b: &Point| rotation(p, a, b) < 0.;
+    if below(v(1), v(0)) && !above(v(n - 1), v(0)) {
+        return 0;
+    }
+    let (mut a, mut b) = (0, n);
+    while b - a > 1 {
+        let c = (a + b) / 2;
+        let down_c = below(v(c + 1), v(c));
+        if down_c && !above(v(c - 1), v(c)) {
+            return c;
+        }
+        let up_a = above(v(a + 1), v(a));
+        let select_left = if up_a {
+            down_c || above(v(a), v(c))
+        } else {
+            down_c && below(v(a), v(c))
+        };
+        if select_left {
+            b = c;
+        } else {
+            a = c;
+        }
+    }
+    a
+}
+
+fn find_tangent(hull: &[Point], p: &Point) -> Point {
+    if let Some(i) = hull.iter().position(|a| a == p) {
+        // the hull containing `p` is left of its next edge
+        hull[(i + 1) % hull.len()]
+    } else if hull.len() < 3 {
+        *hull
+            .iter()
+            .fold(None, |best: Option<&Point>, a| match best {
+                Some(b) if !is_better(p, b, a) => Some(b),
+                _ => Some(a),
+            })
+            .unwrap()
+    } else {
+        hull[tangent(hull, p)]
+    }
+}
+
+pub struct Chan;
+
+impl Chan {
+    fn first_guess(points: &[Point]) -> usize {
+        FIRST_GUESS.min(points.len())
+    }
+}
+
+impl Algo for Chan {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        State {
+            m: Chan::first_guess(&points),
+            points,
+            phase: Phase::Group,
+            groups: Vec::new(),
+            left: Vec::new(),
+            building: Vec::new(),
+            mini_hulls: Vec::new(),
+            hull: Vec::new(),
+            best: None,
+            next_hull: 0,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let action = match state.phase {
+            Phase::Done => Action::NoAction,
+            Phase::Group => {
+                state.groups = state.points.chunks(state.m).map(|g| g.to_vec()).collect();
+                state.mini_hulls.clear();
+                state.phase = Phase::MiniHulls;
+                let action = Action::Group((state.m, state.groups.clone()));
+                state.groups.reverse();
+                action
+            }
+            Phase::MiniHulls => {
+                if state.left.is_empty() {
+                    state.left = state.groups.pop().unwrap();
+                    sort_by_angle(&mut state.left);
+                }
+                let action = step(&mut state.left, &mut state.building, |a, b, c| {
+                    rotation(a, b, c) > 0.
+                });
+                if state.left.is_empty() {
+                    state.mini_hulls.push(std::mem::take(&mut state.building));
+                    if state.groups.is_empty() {
+                        let start = *state.points.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                        state.hull = vec![start];
+                        state.phase = Phase::Wrap;
+                    }
+                }
+                Action::MiniHull(action)
+            }
+            Phase::Wrap => {
+                let cur = *state.hull.last().unwrap();
+                if state.next_hull < state.mini_hulls.len() {
+                    let mini_hull = &state.mini_hulls[state.next_hull];
+                    state.next_hull += 1;
+                    let candidate = find_tangent(mini_hull, &cur);
+                    let best = state.best.unwrap_or(candidate);
+                    let better = best == candidate || is_better(&cur, &best, &candidate);
+                    if better {
+                        state.best = Some(candidate);
+                    }
+                    Action::Tangent((cur, mini_hull.clone(), candidate, best, better))
+                } else {
+                    let best = state.best.take().unwrap();
+                    state.next_hull = 0;
+                    if best == state.hull[0] {
+                        state.phase = Phase::Done;
+                        Action::Accept(Pair::new(cur, best))
+                    } else if state.hull.len() == state.m {
+                        let failed = std::mem::take(&mut state.hull);
+                        state.m = (state.m * state.m).min(state.points.len());
+                        state.phase = Phase::Group;
+                        Action::FailedGuess((failed, state.m))
+                    } else {
+                        state.hull.push(best);
+                        Action::Accept(Pair::new(cur, best))
+                    }
+                }
+            }
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        matches!(state.phase, Phase::Done)
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        for mini_hull in &state.mini_hulls {
+            draw_hull(dc, mini_hull, BLUE_COLOR);
+        }
+        draw_progress(dc, &state.left, &state.building);
+        dc.draw_path(&state.hull, GREEN_COLOR);
+        if Self::is_final(state) {
+            dc.draw_line(&state.hull[0], state.hull.last().unwrap(), GREEN_COLOR);
+        } else if let (Some(best), Some(cur)) = (&state.best, state.hull.last()) {
+            dc.draw_line(cur, best, YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Group((_, groups)) => {
+                for group in groups {
+                    let lb = group
+                        .iter()
+                        .fold(Point::new(MAX_X, MAX_Y), |a, b| a.min(*b));
+                    let rt = group.iter().fold(Point::new(0., 0.), |a, b| a.max(*b));
+                    dc.fill_rect(&lb, &rt, GREEN_COLOR);
+                    for point in group {
+                        dc.draw_point(point, YELLOW_COLOR);
+                    }
+                }
+            }
+            Action::MiniHull(action) => draw_graham_action(dc, action),
+            Action::Tangent((cur, mini_hull, candidate, best, better)) => {
+                draw_hull(dc, mini_hull, YELLOW_COLOR);
+                if *better {
+                    dc.draw_line(cur, best, RED_COLOR);
+                    dc.draw_line(cur, candidate, GREEN_COLOR);
+                } else {
+                    dc.draw_line(cur, best, GREEN_COLOR);
+                    dc.draw_line(cur, candidate, RED_COLOR);
+                }
+                dc.draw_point(candidate, YELLOW_COLOR);
+            }
+            Action::Accept(edge) => dc.draw_path(&[edge.a, edge.b], GREEN_COLOR),
+            Action::FailedGuess((hull, _)) => dc.draw_path(hull, RED_COLOR),
+        }
+    }
+}
+
+fn draw_hull(dc: &mut dyn Canvas, hull: &[Point], color: SolidSource) {
+    dc.draw_path(hull, color);
+    if hull.len() > 2 {
+        dc.draw_line(&hull[0], hull.last().unwrap(), color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn graham_hull(mut points: Vec<Point>) -> Vec<Point> {
+        sort_by_angle(&mut points);
+        let mut hull = Vec::new();
+        while !points.is_empty() {
+            step(&mut points, &mut hull, |a, b, c| rotation(a, b, c) > 0.);
+        }
+        hull
+    }
+
+    #[test]
+    fn tangent_matches_brute_force() {
+        for seed in 0..1000 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut points = Uniform.generate(20, &mut rng);
+            let p = points.pop().unwrap();
+            let hull = graham_hull(points);
+            let is_inside =
+                (0..hull.len()).all(|i| rotation(&hull[i], &hull[(i + 1) % hull.len()], &p) > 0.);
+            if is_inside {
+                continue;
+            }
+            let t = hull[tangent(&hull, &p)];
+            assert!(
+                hull.iter().all(|a| rotation(&p, &t, a) >= 0.),
+                "seed {}: {:?} isn't a tangent from {:?} to {:?}",
+                seed,
+                t,
+                p,
+                hull
+            );
+        }
+    }
+
+    #[test]
+    fn chan_finds_the_same_hull_as_graham() {
+        for seed in 0..20 {
+            let points = Uniform.generate(60, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<Chan>(points.clone());
+            assert_eq!(
+                graham_hull(points),
+                states.last().unwrap().hull,
+                "seed {}",
+                seed
+            );
+        }
+    }
+}
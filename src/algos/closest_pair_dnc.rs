@@ -1,6 +1,6 @@
 use crate::algos::Algo;
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 
 #[derive(Copy, Clone, Debug)]
 enum StackState {
@@ -42,6 +42,7 @@ pub struct ClosestPairDivideAndConquer;
 impl Algo for ClosestPairDivideAndConquer {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
     fn first_state(mut points: Vec<Point>) -> State {
         points.sort_unstable_by(cmp_by_x);
@@ -66,7 +67,7 @@ impl Algo for ClosestPairDivideAndConquer {
                 if borders.r - borders.l <= 3 {
                     let best = brute_force(&borders, &state.points);
                     let hor_borders = HorBorders::from_indexes(&state.points, &borders);
-                    (&mut state.points[borders.l..borders.r]).sort_unstable_by(cmp_by_y);
+                    state.points[borders.l..borders.r].sort_unstable_by(cmp_by_y);
                     state.result.push(best);
                     Action::PrimitiveSolve((hor_borders, best))
                 } else {
@@ -98,7 +99,7 @@ impl Algo for ClosestPairDivideAndConquer {
                     right_best
                 };
                 // TODO: rewrite to merge sort
-                (&mut state.points[borders.l..borders.r]).sort_unstable_by(cmp_by_y);
+                state.points[borders.l..borders.r].sort_unstable_by(cmp_by_y);
                 let h = best.square_len().sqrt();
                 let mut border_points: Vec<Point> = Vec::new();
                 let mut left_x = MAX_X;
@@ -137,7 +138,7 @@ impl Algo for ClosestPairDivideAndConquer {
         state.stack.is_empty()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         for point in &state.points {
             dc.draw_point(point, WHITE_COLOR);
         }
@@ -146,7 +147,7 @@ impl Algo for ClosestPairDivideAndConquer {
         }
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         match action {
             Action::NoAction => {}
             Action::Divide((borders, x)) => {
@@ -1,6 +1,6 @@
 use crate::algos::Algo;
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 use std::cmp::Ordering;
 use std::collections::BTreeSet;
 use std::ops::Bound;
@@ -61,6 +61,7 @@ pub struct ClosestPairSweepLine;
 impl Algo for ClosestPairSweepLine {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
     fn first_state(mut points: Vec<Point>) -> State {
         points.sort_unstable_by(cmp_by_x);
@@ -107,7 +108,7 @@ impl Algo for ClosestPairSweepLine {
         state.right_index >= state.points.len()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         for point in &state.points {
             dc.draw_point(point, WHITE_COLOR);
         }
@@ -116,11 +117,11 @@ impl Algo for ClosestPairSweepLine {
         }
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         match action {
             Action::NoAction => {}
             Action::Scan((p, h)) => {
-                if *h != std::f32::INFINITY {
+                if *h != f32::INFINITY {
                     let lb = Point::new((p.x - h).max(0.), (p.y - h).max(0.));
                     let rt = Point::new(p.x, (p.y + h).min(MAX_Y));
                     dc.fill_rect(&lb, &rt, GREEN_COLOR);
@@ -1,6 +1,6 @@
 use crate::algos::Algo;
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 
 #[derive(Copy, Clone, Debug)]
 enum StackState {
@@ -107,6 +107,7 @@ pub struct ConvexHullDivideAndConquer;
 impl Algo for ConvexHullDivideAndConquer {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
     fn first_state(mut points: Vec<Point>) -> State {
         points.sort_unstable_by(cmp_by_x);
@@ -188,7 +189,7 @@ impl Algo for ConvexHullDivideAndConquer {
         state.stack.is_empty()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         for point in &state.points {
             dc.draw_point(point, WHITE_COLOR);
         }
@@ -197,7 +198,7 @@ impl Algo for ConvexHullDivideAndConquer {
         }
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         match action {
             Action::NoAction => {}
             Action::Divide((borders, x)) => {
@@ -205,19 +206,19 @@ impl Algo for ConvexHullDivideAndConquer {
                 dc.draw_borders(borders);
             }
             Action::Conquer((left, right, upper, lower)) => {
-                draw_hull(dc, &left);
-                draw_hull(dc, &right);
+                draw_hull(dc, left);
+                draw_hull(dc, right);
                 dc.draw_line(&upper.a, &upper.b, GREEN_COLOR);
                 dc.draw_line(&lower.a, &lower.b, GREEN_COLOR);
             }
             Action::PrimitiveSolve(hull) => {
-                draw_hull(dc, &hull);
+                draw_hull(dc, hull);
             }
         }
     }
 }
 
-fn draw_hull(dc: &mut DrawContext, x: &[Point]) {
+fn draw_hull(dc: &mut dyn Canvas, x: &[Point]) {
     dc.draw_path(x, BLUE_COLOR);
     dc.draw_line(x.first().unwrap(), x.last().unwrap(), BLUE_COLOR);
 }
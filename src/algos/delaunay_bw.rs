@@ -0,0 +1,309 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+use raqote::SolidSource;
+
+// Vertices are kept in counterclockwise order
+pub type Triangle = [Point; 3];
+
+#[derive(Clone, Debug)]
+enum Phase {
+    Insert,
+    Cavity(Point, Vec<Triangle>),
+    Fan(Point, Vec<Pair>),
+    Cleanup,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    super_triangle: Triangle,
+    triangles: Vec<Triangle>,
+    next: usize,
+    phase: Phase,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    BadTriangles((Point, Vec<Triangle>)),
+    Cavity((Point, Vec<Pair>)),
+    Fan((Point, Vec<Triangle>)),
+    RemoveSuper(Vec<Triangle>),
+}
+
+// Vertices of the super-triangle are treated as infinitely far, so circles through them
+// turn into half-planes and the triangles on the hull aren't lost
+fn is_bad(triangle: &Triangle, p: &Point) -> bool {
+    let super_triangle = DelaunayBowyerWatson::super_triangle();
+    let real: Vec<usize> = (0..3)
+        .filter(|i| !super_triangle.contains(&triangle[*i]))
+        .collect();
+    match real.as_slice() {
+        [_, _, _] => in_circle(&triangle[0], &triangle[1], &triangle[2], p) > 0.,
+        [i, j] => {
+            // the super vertex is to the left of the edge between the real ones
+            let (a, b) = if (i + 1) % 3 == *j {
+                (triangle[*i], triangle[*j])
+            } else {
+                (triangle[*j], triangle[*i])
+            };
+            let r = exact_rotation(&a, &b, p);
+            r > 0. || (r == 0. && (*p - a).dot(*p - b) < 0.)
+        }
+        [i] => {
+            // super vertices at s * u and s * v, the circle through them and `q` tends to the
+            // half-plane x.c > 0 around `q`, where `c` is the circumcenter of 0, u and v
+            let q = triangle[*i];
+            let u = direction(&triangle[(i + 1) % 3]);
+            let v = direction(&triangle[(i + 2) % 3]);
+            let c = circumcircle(&Point::origin(), &u, &v)
+                .unwrap()
+                .0
+                .to_vector();
+            let x = *p - q;
+            let along = x.dot(c);
+            if along != 0. {
+                return along > 0.;
+            }
+            // on the boundary line the next term of the center decides, it is `k * w`
+            // with u.w = v.w = 1
+            let det = u.x * v.y - u.y * v.x;
+            let w = euclid::vec2(v.y - u.y, u.x - v.x) / det;
+            let k = c.dot(q.to_vector());
+            x.square_length() < 2. * x.dot(w * k - q.to_vector())
+        }
+        _ => true,
+    }
+}
+
+// Rounding in `in_circle` can leave `p` on the wrong side of a cavity edge, then the triangle
+// behind that edge is bad too, otherwise the new triangles would overlap
+fn bad_triangles(triangles: &[Triangle], p: &Point) -> Vec<Triangle> {
+    let mut bad: Vec<Triangle> = triangles.iter().filter(|t| is_bad(t, p)).copied().collect();
+    loop {
+        let mut behind: Vec<Triangle> = Vec::new();
+        for e in cavity(&bad) {
+            if exact_rotation(&e.a, &e.b, p) > 0. {
+                continue;
+            }
+            let neighbor = triangles
+                .iter()
+                .find(|t| !bad.contains(t) && edges(t).any(|o| o.a == e.b && o.b == e.a));
+            if let Some(t) = neighbor {
+                if !behind.contains(t) {
+                    behind.push(*t);
+                }
+            }
+        }
+        if behind.is_empty() {
+            return bad;
+        }
+        bad.extend(behind);
+    }
+}
+
+fn edges(triangle: &Triangle) -> impl Iterator<Item = Pair> + '_ {
+    (0..3).map(move |i| Pair::new(triangle[i], triangle[(i + 1) % 3]))
+}
+
+// Edges of bad triangles which aren't shared with another bad triangle, in no particular order.
+// Each keeps the direction of its triangle, so the cavity is on its left
+fn cavity(bad: &[Triangle]) -> Vec<Pair> {
+    let all: Vec<Pair> = bad.iter().flat_map(edges).collect();
+    all.iter()
+        .filter(|e| !all.iter().any(|o| o.a == e.b && o.b == e.a))
+        .copied()
+        .collect()
+}
+
+// The super-triangle only has to contain all of the points, `is_bad` uses the directions of its
+// vertices, which are their coordinates divided by the scale
+fn super_scale() -> f32 {
+    20. * MAX_X.max(MAX_Y)
+}
+
+fn direction(super_vertex: &Point) -> Point {
+    *super_vertex / super_scale()
+}
+
+pub struct DelaunayBowyerWatson;
+
+impl DelaunayBowyerWatson {
+    fn super_triangle() -> Triangle {
+        let s = super_scale();
+        [
+            Point::new(-s, -s),
+            Point::new(3. * s, -s),
+            Point::new(-s, 3. * s),
+        ]
+    }
+}
+
+impl Algo for DelaunayBowyerWatson {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let super_triangle = Self::super_triangle();
+        State {
+            points,
+            super_triangle,
+            triangles: vec![super_triangle],
+            next: 0,
+            phase: Phase::Insert,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let phase = std::mem::replace(&mut state.phase, Phase::Done);
+        let action = match phase {
+            Phase::Done => Action::NoAction,
+            Phase::Insert => {
+                let p = state.points[state.next];
+                state.next += 1;
+                let bad = bad_triangles(&state.triangles, &p);
+                state.phase = Phase::Cavity(p, bad.clone());
+                Action::BadTriangles((p, bad))
+            }
+            Phase::Cavity(p, bad) => {
+                let good = state
+                    .triangles
+                    .iter()
+                    .filter(|t| !bad.contains(t))
+                    .copied()
+                    .collect();
+                state.triangles = good;
+                let boundary = cavity(&bad);
+                state.phase = Phase::Fan(p, boundary.clone());
+                Action::Cavity((p, boundary))
+            }
+            Phase::Fan(p, boundary) => {
+                let fan: Vec<Triangle> = boundary.iter().map(|e| [e.a, e.b, p]).collect();
+                state.triangles.extend(fan.iter());
+                state.phase = if state.next < state.points.len() {
+                    Phase::Insert
+                } else {
+                    Phase::Cleanup
+                };
+                Action::Fan((p, fan))
+            }
+            Phase::Cleanup => {
+                let super_triangle = state.super_triangle;
+                let (removed, kept) = state
+                    .triangles
+                    .iter()
+                    .partition(|t| t.iter().any(|p| super_triangle.contains(p)));
+                state.triangles = kept;
+                Action::RemoveSuper(removed)
+            }
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        matches!(state.phase, Phase::Done)
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for triangle in &state.triangles {
+            let color = if triangle.iter().any(|p| state.super_triangle.contains(p)) {
+                GRAY_COLOR
+            } else {
+                BLUE_COLOR
+            };
+            draw_triangle(dc, triangle, color);
+        }
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        if let Phase::Fan(p, boundary) = &state.phase {
+            for e in boundary {
+                dc.draw_line(&e.a, &e.b, YELLOW_COLOR);
+            }
+            dc.draw_point(p, YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::BadTriangles((p, bad)) => {
+                for triangle in bad {
+                    draw_triangle(dc, triangle, RED_COLOR);
+                    if let Some((center, r)) =
+                        circumcircle(&triangle[0], &triangle[1], &triangle[2])
+                    {
+                        dc.draw_circle(&center, r, RED_COLOR);
+                    }
+                }
+                dc.draw_point(p, YELLOW_COLOR);
+            }
+            Action::Cavity((p, boundary)) => {
+                for e in boundary {
+                    dc.draw_line(&e.a, &e.b, YELLOW_COLOR);
+                }
+                dc.draw_point(p, YELLOW_COLOR);
+            }
+            Action::Fan((p, fan)) => {
+                for triangle in fan {
+                    draw_triangle(dc, triangle, GREEN_COLOR);
+                }
+                dc.draw_point(p, GREEN_COLOR);
+            }
+            Action::RemoveSuper(removed) => {
+                for triangle in removed {
+                    draw_triangle(dc, triangle, RED_COLOR);
+                }
+            }
+        }
+    }
+}
+
+fn draw_triangle(dc: &mut dyn Canvas, triangle: &Triangle, color: SolidSource) {
+    dc.draw_polyline(&[triangle[0], triangle[1], triangle[2], triangle[0]], color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::algos::graham_andrew::convex_hull;
+    use crate::generators::{Bands, Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn circumcircles_are_empty() {
+        // points in thin bands make flat triangles on the hull
+        let bands = Bands {
+            bands: 3,
+            sigma: 0.05,
+        };
+        for seed in 0..40 {
+            let points = if seed % 2 == 0 {
+                Uniform.generate(40, &mut StdRng::seed_from_u64(seed))
+            } else {
+                bands.generate(40, &mut StdRng::seed_from_u64(seed))
+            };
+            let (states, _) = all_states::<DelaunayBowyerWatson>(points.clone());
+            let triangles = &states.last().unwrap().triangles;
+            // no triangle is lost with the super-triangle
+            let hull = convex_hull(points.clone()).len();
+            assert_eq!(
+                2 * points.len() - 2 - hull,
+                triangles.len(),
+                "seed {}",
+                seed
+            );
+            for t in triangles {
+                assert!(exact_rotation(&t[0], &t[1], &t[2]) > 0., "seed {}", seed);
+                for p in &points {
+                    assert!(!is_bad(t, p), "seed {}: {:?} is inside {:?}", seed, p, t);
+                }
+            }
+        }
+    }
+}
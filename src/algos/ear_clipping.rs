@@ -0,0 +1,161 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Clone, Debug)]
+pub struct State {
+    polygon: Vec<Point>,
+    remaining: Vec<Point>,
+    diagonals: Vec<Pair>,
+    cur: usize,
+    // candidates rejected in a row, the polygon isn't simple if all of them are
+    rejected: usize,
+    done: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Reflex([Point; 3]),
+    Blocked(([Point; 3], Vec<Point>)),
+    Ear([Point; 3]),
+}
+
+pub fn is_reflex(prev: &Point, cur: &Point, next: &Point) -> bool {
+    rotation(prev, cur, next) <= 0.
+}
+
+fn is_in_triangle(t: &[Point; 3], p: &Point) -> bool {
+    (0..3).all(|i| rotation(&t[i], &t[(i + 1) % 3], p) >= 0.)
+}
+
+pub struct EarClipping;
+
+impl Algo for EarClipping {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(mut polygon: Vec<Point>) -> State {
+        if signed_area(&polygon) < 0. {
+            polygon.reverse();
+        }
+        State {
+            remaining: polygon.clone(),
+            polygon,
+            diagonals: Vec::new(),
+            cur: 0,
+            rejected: 0,
+            done: false,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.done {
+            return (state, Action::NoAction);
+        }
+        let n = state.remaining.len();
+        let at = |i: usize| state.remaining[i % n];
+        let i = state.cur;
+        let ear = [at(i + n - 1), at(i), at(i + 1)];
+        let action = if is_reflex(&ear[0], &ear[1], &ear[2]) {
+            Action::Reflex(ear)
+        } else {
+            // only reflex vertices can be inside of a convex corner of a simple polygon
+            let blocking: Vec<Point> = (0..n)
+                .filter(|j| is_reflex(&at(j + n - 1), &at(*j), &at(j + 1)))
+                .map(at)
+                .filter(|p| !ear.contains(p) && is_in_triangle(&ear, p))
+                .collect();
+            if blocking.is_empty() {
+                Action::Ear(ear)
+            } else {
+                Action::Blocked((ear, blocking))
+            }
+        };
+
+        if let Action::Ear(ear) = action {
+            state.remaining.remove(i);
+            state.diagonals.push(Pair::new(ear[0], ear[2]));
+            state.cur = i % state.remaining.len();
+            state.rejected = 0;
+            state.done = state.remaining.len() == 3;
+        } else {
+            state.cur = (i + 1) % n;
+            state.rejected += 1;
+            state.done = state.rejected == n;
+        }
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        dc.draw_path(&state.polygon, WHITE_COLOR);
+        if let (Some(first), Some(last)) = (state.polygon.first(), state.polygon.last()) {
+            dc.draw_line(last, first, WHITE_COLOR);
+        }
+        for diagonal in &state.diagonals {
+            dc.draw_line(&diagonal.a, &diagonal.b, BLUE_COLOR);
+        }
+        if !state.done {
+            dc.draw_point(&state.remaining[state.cur], YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Reflex(ear) => {
+                dc.draw_path(ear, RED_COLOR);
+            }
+            Action::Blocked((ear, blocking)) => {
+                dc.draw_path(&[ear[0], ear[1], ear[2], ear[0]], YELLOW_COLOR);
+                for point in blocking {
+                    dc.draw_point(point, RED_COLOR);
+                }
+            }
+            Action::Ear(ear) => {
+                dc.draw_path(ear, YELLOW_COLOR);
+                dc.draw_path(&[ear[0], ear[2]], GREEN_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, SimplePolygon};
+    use crate::input::to_polygon;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn triangulates_simple_polygons() {
+        // small polygons are the most likely to be degenerate, so they get more seeds
+        for seed in 0..200 {
+            let n = if seed < 20 { 30 } else { 4 + seed as usize % 3 };
+            let polygon = SimplePolygon.generate(n, &mut StdRng::seed_from_u64(seed));
+            // simple and counterclockwise already
+            assert_eq!(
+                Ok(polygon.clone()),
+                to_polygon(polygon.clone()),
+                "seed {}",
+                seed
+            );
+            let (states, actions) = all_states::<EarClipping>(polygon.clone());
+            assert_eq!(polygon.len() - 3, states.last().unwrap().diagonals.len());
+            let mut area = signed_area(&states.last().unwrap().remaining);
+            for action in actions {
+                if let Action::Ear(ear) = action {
+                    area += signed_area(&ear);
+                }
+            }
+            assert!((area - signed_area(&polygon)).abs() < 1e-3, "seed {}", seed);
+        }
+    }
+}
@@ -1,8 +1,8 @@
 pub use crate::algos::graham_common::Action;
-use crate::algos::graham_common::{draw_graham_action, draw_progress, step};
+use crate::algos::graham_common::{draw_graham_action, draw_progress, sort_by_angle, step};
 use crate::algos::Algo;
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 
 pub struct Graham;
 
@@ -15,23 +15,10 @@ pub struct State {
 impl Algo for Graham {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
     fn first_state(mut points: Vec<Point>) -> State {
-        let (leftmost_idx, p0) = points
-            .iter()
-            .enumerate()
-            .min_by(|(_, a), (_, b)| cmp_by_xy(a, b))
-            .map(|(a, b)| (a, *b))
-            .unwrap();
-        let last_idx = points.len() - 1;
-        points.swap(leftmost_idx, last_idx);
-        points[..last_idx].sort_unstable_by(|a, b| {
-            rotation(&p0, a, b).partial_cmp(&0.).unwrap().then_with(|| {
-                let dist_a = (p0 - *a).length();
-                let dist_b = (p0 - *b).length();
-                dist_b.partial_cmp(&dist_a).unwrap()
-            })
-        });
+        sort_by_angle(&mut points);
         State {
             points,
             hull: Vec::new(),
@@ -52,14 +39,14 @@ impl Algo for Graham {
         state.points.is_empty()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         draw_progress(dc, &state.points, &state.hull);
         if Self::is_final(state) {
             dc.draw_line(&state.hull[0], state.hull.last().unwrap(), BLUE_COLOR);
         }
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         draw_graham_action(dc, action);
     }
 }
@@ -1,11 +1,24 @@
 pub use crate::algos::graham_common::Action;
 use crate::algos::graham_common::{draw_graham_action, draw_progress, step};
-use crate::algos::Algo;
+use crate::algos::{all_states, Algo};
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 
 pub struct GrahamAndrew;
 
+// Counterclockwise hull starting from the leftmost point
+pub fn convex_hull(points: Vec<Point>) -> Vec<Point> {
+    let (states, _) = all_states::<GrahamAndrew>(points);
+    let state = states.last().unwrap();
+    let mut hull = state.lower.clone();
+    hull.extend(state.upper.iter().rev().skip(1));
+    // the leftmost point ends both chains
+    if hull.len() > 1 {
+        hull.pop();
+    }
+    hull
+}
+
 #[derive(Clone, Debug)]
 pub struct State {
     left_upper: Vec<Point>,
@@ -17,9 +30,10 @@ pub struct State {
 impl Algo for GrahamAndrew {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
     fn first_state(mut points: Vec<Point>) -> State {
-        points.sort_unstable_by(|a, b| cmp_by_x(b, a));
+        points.sort_unstable_by(|a, b| cmp_by_xy(b, a));
         State {
             left_upper: points.clone(),
             left_lower: points,
@@ -47,7 +61,7 @@ impl Algo for GrahamAndrew {
         state.left_upper.is_empty() && state.left_lower.is_empty()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         if !state.left_upper.is_empty() || state.lower.is_empty() {
             draw_progress(dc, &state.left_upper, &state.upper);
         } else {
@@ -56,7 +70,41 @@ impl Algo for GrahamAndrew {
         };
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         draw_graham_action(dc, action);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{Distribution, Grid, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Grid points share x, so the sort has to break ties by y for the chains to start at the corners
+    #[test]
+    fn builds_convex_hull() {
+        for seed in 0..20 {
+            let uniform = Uniform.generate(30, &mut StdRng::seed_from_u64(seed));
+            let grid = Grid.generate(30, &mut StdRng::seed_from_u64(seed));
+            for points in [uniform, grid] {
+                let hull = convex_hull(points.clone());
+                let leftmost = points.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                assert_eq!(*leftmost, hull[0], "seed {}", seed);
+                let n = hull.len();
+                for i in 0..n {
+                    let (a, b, c) = (hull[i], hull[(i + 1) % n], hull[(i + 2) % n]);
+                    assert!(rotation(&a, &b, &c) > 0., "seed {}", seed);
+                    assert!(
+                        points.iter().all(|p| rotation(&a, &b, p) >= 0.),
+                        "seed {}: {:?} -> {:?} isn't a hull edge",
+                        seed,
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+    }
+}
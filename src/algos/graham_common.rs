@@ -1,5 +1,5 @@
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 
 #[derive(Copy, Clone, Debug)]
 pub enum Action {
@@ -10,6 +10,26 @@ pub enum Action {
     RejectLine((Point, Point, Point)),
 }
 
+// Moves the leftmost point to the end and sorts the rest by angle around it,
+// so `step` pops points in the order of Graham scan
+pub fn sort_by_angle(points: &mut [Point]) {
+    let (leftmost_idx, p0) = points
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| cmp_by_xy(a, b))
+        .map(|(a, b)| (a, *b))
+        .unwrap();
+    let last_idx = points.len() - 1;
+    points.swap(leftmost_idx, last_idx);
+    points[..last_idx].sort_unstable_by(|a, b| {
+        rotation(&p0, a, b).partial_cmp(&0.).unwrap().then_with(|| {
+            let dist_a = (p0 - *a).length();
+            let dist_b = (p0 - *b).length();
+            dist_b.partial_cmp(&dist_a).unwrap()
+        })
+    });
+}
+
 pub fn step<F>(left: &mut Vec<Point>, result: &mut Vec<Point>, is_convex: F) -> Action
 where
     F: Fn(&Point, &Point, &Point) -> bool,
@@ -43,14 +63,14 @@ where
     }
 }
 
-pub fn draw_progress(dc: &mut DrawContext, left: &[Point], done: &[Point]) {
+pub fn draw_progress(dc: &mut dyn Canvas, left: &[Point], done: &[Point]) {
     for point in left {
         dc.draw_point(point, WHITE_COLOR);
     }
     dc.draw_path(done, BLUE_COLOR);
 }
 
-pub fn draw_graham_action(dc: &mut DrawContext, action: &Action) {
+pub fn draw_graham_action(dc: &mut dyn Canvas, action: &Action) {
     match action {
         Action::NoAction => {}
         Action::AcceptPoint(p) => dc.draw_point(p, GREEN_COLOR),
@@ -0,0 +1,279 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+use std::collections::VecDeque;
+
+// Points closer to the boundary than this are still inside of a half-plane
+const TOLERANCE: f32 = 1e-4;
+
+// Lines through the points facing the center of the area, so the intersection is never empty
+pub fn tangent_lines(points: Vec<Point>) -> Vec<Line> {
+    let center = Rect::full().center();
+    points
+        .into_iter()
+        .filter(|p| (*p - center).length() > TOLERANCE)
+        .map(Line::facing_center)
+        .collect()
+}
+
+fn box_lines() -> [Line; 4] {
+    let corners = [
+        Point::new(0., 0.),
+        Point::new(MAX_X, 0.),
+        Point::new(MAX_X, MAX_Y),
+        Point::new(0., MAX_Y),
+    ];
+    [
+        Line::new(corners[0], corners[1]),
+        Line::new(corners[1], corners[2]),
+        Line::new(corners[2], corners[3]),
+        Line::new(corners[3], corners[0]),
+    ]
+}
+
+fn is_outside(line: &Line, p: &Point) -> bool {
+    line.distance(p) < -TOLERANCE
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Phase {
+    Add,
+    Close,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    // sorted by angle, the sides of the view box keep the intersection bounded
+    lines: Vec<Line>,
+    next: usize,
+    deque: VecDeque<usize>,
+    region: Vec<Point>,
+    phase: Phase,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Push(Line),
+    // parallel to the back of the deque and weaker than it
+    Skip(Line),
+    // the line which cuts off a vertex, the popped line and the vertex
+    PopBack((Line, Line, Point)),
+    PopFront((Line, Line, Point)),
+    Empty((Line, Line)),
+    Region(Vec<Point>),
+}
+
+impl State {
+    fn vertex(&self, i: usize, j: usize) -> Option<Point> {
+        self.lines[self.deque[i]].intersection(&self.lines[self.deque[j]])
+    }
+
+    // Vertex between the last two lines if it is outside of `line`
+    fn check_back(&self, line: &Line) -> Option<Point> {
+        let n = self.deque.len();
+        self.vertex(n - 2, n - 1).filter(|v| is_outside(line, v))
+    }
+
+    fn check_front(&self, line: &Line) -> Option<Point> {
+        self.vertex(0, 1).filter(|v| is_outside(line, v))
+    }
+
+    fn pop_back(&mut self, line: Line, v: Point) -> Action {
+        let popped = self.deque.pop_back().unwrap();
+        Action::PopBack((line, self.lines[popped], v))
+    }
+
+    fn pop_front(&mut self, line: Line, v: Point) -> Action {
+        let popped = self.deque.pop_front().unwrap();
+        Action::PopFront((line, self.lines[popped], v))
+    }
+
+    fn add_step(&mut self) -> Action {
+        let line = self.lines[self.next];
+        if self.deque.len() > 1 {
+            if let Some(v) = self.check_back(&line) {
+                return self.pop_back(line, v);
+            }
+            if let Some(v) = self.check_front(&line) {
+                return self.pop_front(line, v);
+            }
+        }
+        if let Some(&back) = self.deque.back() {
+            let last = self.lines[back];
+            if line.is_parallel(&last) {
+                if (line.b - line.a).dot(last.b - last.a) < 0. {
+                    // opposite lines next to each other in the deque don't overlap
+                    self.phase = Phase::Done;
+                    return Action::Empty((line, last));
+                }
+                if is_outside(&line, &last.a) {
+                    return self.pop_back(line, last.a);
+                }
+                self.next += 1;
+                return Action::Skip(line);
+            }
+        }
+        self.deque.push_back(self.next);
+        self.next += 1;
+        Action::Push(line)
+    }
+
+    // The lines at one end may cut off vertices at the other one
+    fn close_step(&mut self) -> Action {
+        let n = self.deque.len();
+        if n > 2 {
+            let (front, back) = (self.lines[self.deque[0]], self.lines[self.deque[n - 1]]);
+            if let Some(v) = self.check_back(&front) {
+                return self.pop_back(front, v);
+            }
+            if let Some(v) = self.check_front(&back) {
+                return self.pop_front(back, v);
+            }
+            self.region = (0..n).filter_map(|i| self.vertex(i, (i + 1) % n)).collect();
+        }
+        self.phase = Phase::Done;
+        Action::Region(self.region.clone())
+    }
+
+    // Vertices between the neighboring lines of the deque
+    fn chain(&self) -> Vec<Point> {
+        (1..self.deque.len())
+            .filter_map(|i| self.vertex(i - 1, i))
+            .collect()
+    }
+}
+
+pub struct HalfPlaneIntersection;
+
+impl Algo for HalfPlaneIntersection {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Line>;
+
+    fn first_state(mut lines: Vec<Line>) -> State {
+        lines.extend(box_lines().iter());
+        lines.sort_by(|a, b| a.angle().partial_cmp(&b.angle()).unwrap());
+        State {
+            lines,
+            next: 0,
+            deque: VecDeque::new(),
+            region: Vec::new(),
+            phase: Phase::Add,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let action = match state.phase {
+            Phase::Add => {
+                let action = state.add_step();
+                if state.phase == Phase::Add && state.next == state.lines.len() {
+                    state.phase = Phase::Close;
+                }
+                action
+            }
+            Phase::Close => state.close_step(),
+            Phase::Done => Action::NoAction,
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.phase == Phase::Done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for line in &state.lines {
+            dc.draw_infinite_line(line, GRAY_COLOR);
+        }
+        if state.phase == Phase::Done {
+            if !state.region.is_empty() {
+                dc.fill_polygon(&state.region, GREEN_COLOR);
+                let mut outline = state.region.clone();
+                outline.push(state.region[0]);
+                dc.draw_path(&outline, GREEN_COLOR);
+            }
+            return;
+        }
+        for i in &state.deque {
+            dc.draw_infinite_line(&state.lines[*i], BLUE_COLOR);
+        }
+        dc.draw_path(&state.chain(), WHITE_COLOR);
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Push(line) => dc.draw_infinite_line(line, GREEN_COLOR),
+            Action::Skip(line) => dc.draw_infinite_line(line, RED_COLOR),
+            Action::PopBack((line, popped, v)) | Action::PopFront((line, popped, v)) => {
+                dc.draw_infinite_line(line, YELLOW_COLOR);
+                dc.draw_infinite_line(popped, RED_COLOR);
+                dc.draw_point(v, RED_COLOR);
+            }
+            Action::Empty((a, b)) => {
+                dc.draw_infinite_line(a, RED_COLOR);
+                dc.draw_infinite_line(b, RED_COLOR);
+            }
+            Action::Region(region) => dc.fill_polygon(region, GREEN_COLOR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Cuts the polygon by every half-plane in turn
+    fn clip(lines: &[Line]) -> Vec<Point> {
+        let mut polygon: Vec<Point> = box_lines().iter().map(|l| l.a).collect();
+        for line in lines {
+            let n = polygon.len();
+            let mut next = Vec::new();
+            for i in 0..n {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                if line.distance(&a) >= 0. {
+                    next.push(a);
+                }
+                if (line.distance(&a) >= 0.) != (line.distance(&b) >= 0.) {
+                    next.extend(line.intersection(&Line::new(a, b)));
+                }
+            }
+            polygon = next;
+        }
+        polygon
+    }
+
+    #[test]
+    fn intersects_half_planes() {
+        for seed in 0..20 {
+            let points = Uniform.generate(30, &mut StdRng::seed_from_u64(seed));
+            let lines = tangent_lines(points);
+            let (states, _) = all_states::<HalfPlaneIntersection>(lines.clone());
+            let region = &states.last().unwrap().region;
+            assert!(signed_area(region) > 0., "seed {}", seed);
+            for v in region {
+                assert!(lines.iter().all(|l| l.distance(v) > -1e-3), "seed {}", seed);
+            }
+            let expected = signed_area(&clip(&lines));
+            assert!(
+                (signed_area(region) - expected).abs() < 1e-3,
+                "seed {}",
+                seed
+            );
+        }
+
+        let outward = vec![
+            Line::new(Point::new(1., 1.), Point::new(1., 2.)),
+            Line::new(Point::new(5., 2.), Point::new(5., 1.)),
+            Line::new(Point::new(1., 1.), Point::new(2., 1.)),
+        ];
+        let (states, _) = all_states::<HalfPlaneIntersection>(outward);
+        assert!(states.last().unwrap().region.is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+// Uninserted point and the start of a hull edge visible from it
+#[derive(Copy, Clone, Debug)]
+struct Conflict {
+    point: Point,
+    edge: Point,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    hull: Vec<Point>,
+    conflicts: Vec<Conflict>,
+    inserting: Option<Point>,
+    done: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Visible((Point, Vec<Pair>)),
+    Tangents((Point, Point, Point, Vec<Point>)),
+}
+
+fn edge(hull: &[Point], i: usize) -> (Point, Point) {
+    (hull[i % hull.len()], hull[(i + 1) % hull.len()])
+}
+
+fn is_visible(hull: &[Point], i: usize, p: &Point) -> bool {
+    let (a, b) = edge(hull, i);
+    rotation(&a, &b, p) < 0.
+}
+
+fn find_conflict(hull: &[Point], p: Point) -> Option<Conflict> {
+    (0..hull.len())
+        .find(|i| is_visible(hull, *i, &p))
+        .map(|i| Conflict {
+            point: p,
+            edge: hull[i],
+        })
+}
+
+// Rotates the hull so that the visible chain starts at index 0, returns the number of visible edges
+fn visible_chain(hull: &mut [Point], conflict: &Conflict) -> usize {
+    let n = hull.len();
+    let mut start = hull.iter().position(|a| *a == conflict.edge).unwrap();
+    while is_visible(hull, start + n - 1, &conflict.point) {
+        start = (start + n - 1) % n;
+    }
+    hull.rotate_left(start);
+    (0..n)
+        .take_while(|i| is_visible(hull, *i, &conflict.point))
+        .count()
+}
+
+pub struct IncrementalHull;
+
+impl Algo for IncrementalHull {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let (a, b) = (points[0], points[1]);
+        let third = points[2..]
+            .iter()
+            .find(|c| !eps_equal(rotation(&a, &b, c), 0.));
+        let (hull, rest) = match third {
+            Some(c) if rotation(&a, &b, c) > 0. => (vec![a, b, *c], c),
+            Some(c) => (vec![a, *c, b], c),
+            None => {
+                // All points are collinear, the hull is the segment between the extremes
+                let lo = *points.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                let hi = *points.iter().max_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                return State {
+                    points,
+                    hull: vec![lo, hi],
+                    conflicts: Vec::new(),
+                    inserting: None,
+                    done: true,
+                };
+            }
+        };
+        let conflicts = points[2..]
+            .iter()
+            .filter(|p| *p != rest)
+            .filter_map(|p| find_conflict(&hull, *p))
+            .collect();
+        State {
+            points,
+            hull,
+            conflicts,
+            inserting: None,
+            done: false,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.done {
+            return (state, Action::NoAction);
+        }
+        let action = match state.inserting.take() {
+            None => {
+                let conflict = state.conflicts[0];
+                let count = visible_chain(&mut state.hull, &conflict);
+                let visible = (0..count)
+                    .map(|i| {
+                        let (a, b) = edge(&state.hull, i);
+                        Pair::new(a, b)
+                    })
+                    .collect();
+                state.inserting = Some(conflict.point);
+                Action::Visible((conflict.point, visible))
+            }
+            Some(p) => {
+                let conflict = state.conflicts.remove(0);
+                let count = visible_chain(&mut state.hull, &conflict);
+                let (l, r) = (state.hull[0], state.hull[count % state.hull.len()]);
+                let removed: Vec<Point> = state.hull.splice(0..count, vec![l, p]).collect();
+                let mut discarded = Vec::new();
+                for c in state.conflicts.iter_mut() {
+                    if !removed.contains(&c.edge) {
+                        continue;
+                    }
+                    if rotation(&l, &p, &c.point) < 0. {
+                        c.edge = l;
+                    } else if rotation(&p, &r, &c.point) < 0. {
+                        c.edge = p;
+                    } else {
+                        discarded.push(c.point);
+                    }
+                }
+                state.conflicts.retain(|c| !discarded.contains(&c.point));
+                state.done = state.conflicts.is_empty();
+                Action::Tangents((l, p, r, discarded))
+            }
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        for conflict in &state.conflicts {
+            let i = state.hull.iter().position(|a| *a == conflict.edge).unwrap();
+            let (a, b) = edge(&state.hull, i);
+            dc.draw_line(&conflict.point, &a.lerp(b, 0.5), GRAY_COLOR);
+        }
+        dc.draw_path(&state.hull, BLUE_COLOR);
+        dc.draw_line(&state.hull[0], state.hull.last().unwrap(), BLUE_COLOR);
+        if let Some(p) = &state.inserting {
+            dc.draw_point(p, YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Visible((p, visible)) => {
+                for e in visible {
+                    dc.draw_line(&e.a, &e.b, RED_COLOR);
+                    dc.draw_line(p, &e.a, YELLOW_COLOR);
+                    dc.draw_line(p, &e.b, YELLOW_COLOR);
+                }
+                dc.draw_point(p, YELLOW_COLOR);
+            }
+            Action::Tangents((l, p, r, discarded)) => {
+                dc.draw_path(&[*l, *p, *r], GREEN_COLOR);
+                for point in discarded {
+                    dc.draw_point(point, RED_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{insertion_order, Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn hull_contains_all_points() {
+        for seed in 0..20 {
+            let points = Uniform.generate(60, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<IncrementalHull>(insertion_order(points, seed));
+            let state = states.last().unwrap();
+            for i in 0..state.hull.len() {
+                let (a, b) = edge(&state.hull, i);
+                assert!(
+                    state.points.iter().all(|p| rotation(&a, &b, p) >= 0.),
+                    "seed {}: {:?} -> {:?} isn't a hull edge",
+                    seed,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
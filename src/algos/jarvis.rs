@@ -0,0 +1,147 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    hull: Vec<Point>,
+    best: Option<Point>,
+    next: usize,
+    done: bool,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Test((Point, Point, Point, bool)),
+    Accept(Pair),
+}
+
+// Candidate wins if it is to the right of the current best direction, so all points end up
+// to the left of the hull edges. For collinear points the farthest one wins to skip points on edges
+pub fn is_better(cur: &Point, best: &Point, candidate: &Point) -> bool {
+    let rot = rotation(cur, best, candidate);
+    rot < 0. || (eps_equal(rot, 0.) && (*candidate - *cur).length() > (*best - *cur).length())
+}
+
+pub struct Jarvis;
+
+impl Algo for Jarvis {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let start = *points.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+        State {
+            points,
+            hull: vec![start],
+            best: None,
+            next: 0,
+            done: false,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.done {
+            return (state, Action::NoAction);
+        }
+        let cur = *state.hull.last().unwrap();
+        while state.next < state.points.len() && state.points[state.next] == cur {
+            state.next += 1;
+        }
+        if state.next < state.points.len() {
+            let candidate = state.points[state.next];
+            state.next += 1;
+            let action = match state.best {
+                None => {
+                    state.best = Some(candidate);
+                    Action::Test((cur, candidate, candidate, true))
+                }
+                Some(best) => {
+                    let better = is_better(&cur, &best, &candidate);
+                    if better {
+                        state.best = Some(candidate);
+                    }
+                    Action::Test((cur, best, candidate, better))
+                }
+            };
+            return (state, action);
+        }
+
+        let best = state.best.take().unwrap();
+        state.next = 0;
+        if best == state.hull[0] {
+            state.done = true;
+        } else {
+            state.hull.push(best);
+        }
+        (state, Action::Accept(Pair::new(cur, best)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        dc.draw_path(&state.hull, BLUE_COLOR);
+        if Self::is_final(state) {
+            dc.draw_line(&state.hull[0], state.hull.last().unwrap(), BLUE_COLOR);
+        }
+        if let Some(best) = &state.best {
+            dc.draw_line(state.hull.last().unwrap(), best, GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Test((cur, best, candidate, better)) => {
+                if *better {
+                    dc.draw_line(cur, best, RED_COLOR);
+                    dc.draw_line(cur, candidate, GREEN_COLOR);
+                } else {
+                    dc.draw_line(cur, best, GREEN_COLOR);
+                    dc.draw_line(cur, candidate, RED_COLOR);
+                }
+                dc.draw_point(candidate, YELLOW_COLOR);
+                dc.draw_point(cur, YELLOW_COLOR);
+            }
+            Action::Accept(edge) => {
+                dc.draw_line(&edge.a, &edge.b, GREEN_COLOR);
+                dc.draw_point(&edge.b, GREEN_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+
+    #[test]
+    fn jarvis_skips_inner_and_collinear_points() {
+        let (states, _) = all_states::<Jarvis>(vec![
+            Point::new(2., 2.),
+            Point::new(1., 1.),
+            Point::new(3., 1.),
+            Point::new(2., 1.),
+            Point::new(3., 3.),
+            Point::new(1., 3.),
+        ]);
+        assert_eq!(
+            vec![
+                Point::new(1., 1.),
+                Point::new(3., 1.),
+                Point::new(3., 3.),
+                Point::new(1., 3.),
+            ],
+            states.last().unwrap().hull
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::algos::{Algo, WithParams};
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    point: Point,
+    // splits by x if vertical, by y otherwise
+    vertical: bool,
+    cell: Rect,
+    children: [Option<usize>; 2],
+}
+
+impl Node {
+    fn split_line(&self) -> Pair {
+        let (p, c) = (self.point, self.cell);
+        if self.vertical {
+            Pair::new(Point::new(p.x, c.lb.y), Point::new(p.x, c.rt.y))
+        } else {
+            Pair::new(Point::new(c.lb.x, p.y), Point::new(c.rt.x, p.y))
+        }
+    }
+
+    fn child_cells(&self) -> [Rect; 2] {
+        let (p, c) = (self.point, self.cell);
+        if self.vertical {
+            [
+                Rect::new(c.lb, Point::new(p.x, c.rt.y)),
+                Rect::new(Point::new(p.x, c.lb.y), c.rt),
+            ]
+        } else {
+            [
+                Rect::new(c.lb, Point::new(c.rt.x, p.y)),
+                Rect::new(Point::new(c.lb.x, p.y), c.rt),
+            ]
+        }
+    }
+
+    // The child on the same side of the splitting line as `p` goes first
+    fn near_far(&self, p: &Point) -> [Option<usize>; 2] {
+        let below = if self.vertical {
+            p.x < self.point.x
+        } else {
+            p.y < self.point.y
+        };
+        if below {
+            self.children
+        } else {
+            [self.children[1], self.children[0]]
+        }
+    }
+}
+
+// Points `l..r` build the subtree attached to the `side` child of `parent`
+#[derive(Copy, Clone, Debug)]
+struct Task {
+    l: usize,
+    r: usize,
+    vertical: bool,
+    cell: Rect,
+    parent: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    query: Point,
+    nodes: Vec<Node>,
+    build: Vec<Task>,
+    search: Vec<usize>,
+    best: Option<Pair>,
+    done: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Split((Rect, Pair, Point)),
+    Visit((Rect, Pair)),
+    Prune(Rect),
+    Answer(Pair),
+}
+
+pub struct KdTree;
+
+impl Algo for KdTree {
+    type State = State;
+    type Action = Action;
+    type Input = WithParams<Vec<Point>, Point>;
+
+    fn first_state(input: Self::Input) -> State {
+        let WithParams {
+            data: points,
+            params: query,
+        } = input;
+        let build = if points.is_empty() {
+            Vec::new()
+        } else {
+            vec![Task {
+                l: 0,
+                r: points.len(),
+                vertical: true,
+                cell: Rect::full(),
+                parent: None,
+            }]
+        };
+        State {
+            points,
+            query,
+            nodes: Vec::new(),
+            build,
+            search: Vec::new(),
+            best: None,
+            done: false,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if let Some(task) = state.build.pop() {
+            let m = (task.l + task.r) / 2;
+            let cmp = if task.vertical { cmp_by_x } else { cmp_by_y };
+            state.points[task.l..task.r].select_nth_unstable_by(m - task.l, cmp);
+            let node = Node {
+                point: state.points[m],
+                vertical: task.vertical,
+                cell: task.cell,
+                children: [None, None],
+            };
+            let index = state.nodes.len();
+            if let Some((parent, side)) = task.parent {
+                state.nodes[parent].children[side] = Some(index);
+            }
+            state.nodes.push(node);
+
+            let cells = node.child_cells();
+            for (side, (l, r)) in [(task.l, m), (m + 1, task.r)].iter().enumerate().rev() {
+                if l < r {
+                    state.build.push(Task {
+                        l: *l,
+                        r: *r,
+                        vertical: !task.vertical,
+                        cell: cells[side],
+                        parent: Some((index, side)),
+                    });
+                }
+            }
+            if state.build.is_empty() {
+                state.search.push(0);
+            }
+            return (
+                state,
+                Action::Split((node.cell, node.split_line(), node.point)),
+            );
+        }
+
+        let action = if let Some(index) = state.search.pop() {
+            let node = state.nodes[index];
+            let dist = node.cell.distance(&state.query);
+            if state
+                .best
+                .is_some_and(|best| dist * dist >= best.square_len())
+            {
+                // nothing in the cell is closer than the current best
+                Action::Prune(node.cell)
+            } else {
+                let candidate = Pair::new(state.query, node.point);
+                if state
+                    .best
+                    .map_or(true, |best| candidate.square_len() < best.square_len())
+                {
+                    state.best = Some(candidate);
+                }
+                // descend to the near child first, the far one is checked on the way back
+                for child in node.near_far(&state.query).iter().rev().flatten() {
+                    state.search.push(*child);
+                }
+                Action::Visit((node.cell, candidate))
+            }
+        } else if !state.done {
+            state.done = true;
+            state.best.map_or(Action::NoAction, Action::Answer)
+        } else {
+            Action::NoAction
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for node in &state.nodes {
+            let line = node.split_line();
+            dc.draw_line(&line.a, &line.b, BLUE_COLOR);
+        }
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        if state.build.is_empty() {
+            if let Some(best) = state.best {
+                dc.draw_circle(&best.a, best.square_len().sqrt(), GREEN_COLOR);
+                dc.draw_path(&[best.a, best.b], GREEN_COLOR);
+            }
+            dc.draw_point(&state.query, YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Split((cell, line, point)) => {
+                dc.fill_rect(&cell.lb, &cell.rt, GREEN_COLOR);
+                dc.draw_line(&line.a, &line.b, RED_COLOR);
+                dc.draw_point(point, RED_COLOR);
+            }
+            Action::Visit((cell, candidate)) => {
+                dc.fill_rect(&cell.lb, &cell.rt, GREEN_COLOR);
+                dc.draw_line(&candidate.a, &candidate.b, YELLOW_COLOR);
+                dc.draw_point(&candidate.b, YELLOW_COLOR);
+            }
+            Action::Prune(cell) => dc.fill_rect(&cell.lb, &cell.rt, RED_COLOR),
+            Action::Answer(best) => {
+                dc.draw_path(&[best.a, best.b], RED_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{random_point, Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn finds_the_nearest_neighbor() {
+        for seed in 0..20 {
+            let points = Uniform.generate(50, &mut StdRng::seed_from_u64(seed));
+            let (states, actions) =
+                all_states::<KdTree>(WithParams::new(points.clone(), random_point(seed)));
+            let state = states.last().unwrap();
+            assert_eq!(points.len(), state.nodes.len());
+            let best = state.best.unwrap().square_len();
+            let expected = points
+                .iter()
+                .map(|p| (*p - state.query).square_length())
+                .fold(f32::INFINITY, f32::min);
+            assert_eq!(expected, best, "seed {}", seed);
+            let visited = actions
+                .iter()
+                .filter(|a| matches!(a, Action::Visit(_)))
+                .count();
+            assert!(visited < points.len(), "seed {}", seed);
+        }
+    }
+}
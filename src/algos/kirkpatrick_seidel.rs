@@ -0,0 +1,316 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+// Lower hull is found as the upper hull of points mirrored by y
+#[derive(Clone, Debug)]
+struct Task {
+    k: Point,
+    m: Point,
+    points: Vec<Point>,
+    lower: bool,
+}
+
+#[derive(Clone, Debug)]
+enum StackState {
+    Split,
+    Prune(f32, Vec<Point>),
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    hull: Vec<Pair>,
+    stack: Vec<(Task, StackState)>,
+}
+
+// Slope of the support line and a point on it
+type Support = (f32, Point);
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Split((HorBorders, f32)),
+    Prune((f32, Vec<Point>, Vec<Point>, Option<Support>)),
+    Bridge((f32, Pair, Vec<Point>, Vec<HorBorders>)),
+}
+
+fn flip(p: &Point, lower: bool) -> Point {
+    if lower {
+        Point::new(p.x, -p.y)
+    } else {
+        *p
+    }
+}
+
+fn flip_all(points: &[Point], lower: bool) -> Vec<Point> {
+    points.iter().map(|p| flip(p, lower)).collect()
+}
+
+fn cmp_f32(a: &f32, b: &f32) -> std::cmp::Ordering {
+    a.partial_cmp(b).unwrap()
+}
+
+fn lower_median(mut values: Vec<f32>) -> f32 {
+    let mid = (values.len() - 1) / 2;
+    *values.select_nth_unstable_by(mid, cmp_f32).1
+}
+
+enum Round {
+    Bridge(Pair),
+    Pruned(Vec<Point>, Vec<Point>, Option<Support>),
+}
+
+// One round of pairing and pruning for the bridge over the vertical line `x = a`
+fn prune(candidates: &[Point], a: f32) -> Round {
+    if candidates.len() == 2 {
+        let mut pair = [candidates[0], candidates[1]];
+        pair.sort_unstable_by(cmp_by_xy);
+        return Round::Bridge(Pair::new(pair[0], pair[1]));
+    }
+    let mut kept = Vec::new();
+    let mut eliminated = Vec::new();
+    let mut pairs = Vec::new();
+    for chunk in candidates.chunks(2) {
+        match *chunk {
+            [p, q] if eps_equal(p.x, q.x) => {
+                let (low, high) = if p.y < q.y { (p, q) } else { (q, p) };
+                kept.push(high);
+                eliminated.push(low);
+            }
+            [p, q] => {
+                let (p, q) = if p.x < q.x { (p, q) } else { (q, p) };
+                pairs.push((p, q, (q.y - p.y) / (q.x - p.x)));
+            }
+            [p] => kept.push(p),
+            _ => unreachable!(),
+        }
+    }
+    if pairs.is_empty() {
+        return Round::Pruned(kept, eliminated, None);
+    }
+
+    let k = lower_median(pairs.iter().map(|(_, _, slope)| *slope).collect());
+    let height = |p: &Point| p.y - k * p.x;
+    let max = candidates.iter().map(height).fold(f32::MIN, f32::max);
+    let support: Vec<&Point> = candidates
+        .iter()
+        .filter(|p| max - height(p) <= 1e-5 * max.abs().max(1.))
+        .collect();
+    let pk = **support.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+    let pm = **support.iter().max_by(|a, b| cmp_by_xy(a, b)).unwrap();
+    if pk.x <= a && pm.x > a {
+        return Round::Bridge(Pair::new(pk, pm));
+    }
+
+    for (p, q, slope) in pairs {
+        if pm.x <= a && slope >= k {
+            kept.push(q);
+            eliminated.push(p);
+        } else if pk.x > a && slope <= k {
+            kept.push(p);
+            eliminated.push(q);
+        } else {
+            kept.push(p);
+            kept.push(q);
+        }
+    }
+    Round::Pruned(kept, eliminated, Some((k, pk)))
+}
+
+fn hull_task(points: &[Point], lower: bool) -> Task {
+    let points = flip_all(points, lower);
+    let k = *points
+        .iter()
+        .min_by(|a, b| cmp_by_x(a, b).then_with(|| cmp_by_y(b, a)))
+        .unwrap();
+    let m = *points
+        .iter()
+        .max_by(|a, b| cmp_by_x(a, b).then_with(|| cmp_by_y(a, b)))
+        .unwrap();
+    let mut above: Vec<Point> = points
+        .into_iter()
+        .filter(|p| rotation(&k, &m, p) > 0.)
+        .collect();
+    above.push(k);
+    above.push(m);
+    Task {
+        k,
+        m,
+        points: above,
+        lower,
+    }
+}
+
+pub struct KirkpatrickSeidel;
+
+impl Algo for KirkpatrickSeidel {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let stack = vec![
+            (hull_task(&points, true), StackState::Split),
+            (hull_task(&points, false), StackState::Split),
+        ];
+        State {
+            points,
+            hull: Vec::new(),
+            stack,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.stack.is_empty() {
+            return (state, Action::NoAction);
+        }
+        let (task, cur) = state.stack.pop().unwrap();
+        let action = match cur {
+            StackState::Split => {
+                let a = lower_median(task.points.iter().map(|p| p.x).collect());
+                let borders = HorBorders::new(task.k.x, task.m.x);
+                let candidates = task.points.clone();
+                state.stack.push((task, StackState::Prune(a, candidates)));
+                Action::Split((borders, a))
+            }
+            StackState::Prune(a, candidates) => match prune(&candidates, a) {
+                Round::Pruned(kept, eliminated, support) => {
+                    let lower = task.lower;
+                    state.stack.push((task, StackState::Prune(a, kept.clone())));
+                    Action::Prune((
+                        a,
+                        flip_all(&kept, lower),
+                        flip_all(&eliminated, lower),
+                        support.map(|(k, p)| if lower { (-k, flip(&p, lower)) } else { (k, p) }),
+                    ))
+                }
+                Round::Bridge(bridge) => {
+                    let (i, j) = (bridge.a, bridge.b);
+                    let lower = task.lower;
+                    let mut parts = Vec::new();
+                    if j != task.m {
+                        let mut points: Vec<Point> =
+                            task.points.iter().filter(|p| p.x > j.x).copied().collect();
+                        points.push(j);
+                        parts.push(HorBorders::new(j.x, task.m.x));
+                        let right = Task {
+                            k: j,
+                            m: task.m,
+                            points,
+                            lower,
+                        };
+                        state.stack.push((right, StackState::Split));
+                    }
+                    if i != task.k {
+                        let mut points: Vec<Point> =
+                            task.points.iter().filter(|p| p.x < i.x).copied().collect();
+                        points.push(i);
+                        parts.push(HorBorders::new(task.k.x, i.x));
+                        let left = Task {
+                            k: task.k,
+                            m: i,
+                            points,
+                            lower,
+                        };
+                        state.stack.push((left, StackState::Split));
+                    }
+                    let between: Vec<Point> = task
+                        .points
+                        .iter()
+                        .filter(|p| p.x > i.x && p.x < j.x)
+                        .copied()
+                        .collect();
+                    let bridge = Pair::new(flip(&i, lower), flip(&j, lower));
+                    state.hull.push(bridge);
+                    Action::Bridge((a, bridge, flip_all(&between, lower), parts))
+                }
+            },
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.stack.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        for edge in &state.hull {
+            dc.draw_line(&edge.a, &edge.b, BLUE_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Split((borders, a)) => {
+                dc.draw_borders(borders);
+                dc.draw_vertical_line(*a, YELLOW_COLOR);
+            }
+            Action::Prune((a, kept, eliminated, support)) => {
+                dc.draw_vertical_line(*a, YELLOW_COLOR);
+                if let Some((k, p)) = support {
+                    dc.draw_line(
+                        &Point::new(0., p.y - k * p.x),
+                        &Point::new(MAX_X, p.y + k * (MAX_X - p.x)),
+                        GREEN_COLOR,
+                    );
+                }
+                for point in kept {
+                    dc.draw_point(point, YELLOW_COLOR);
+                }
+                for point in eliminated {
+                    dc.draw_point(point, RED_COLOR);
+                }
+            }
+            Action::Bridge((a, bridge, between, parts)) => {
+                for borders in parts {
+                    dc.draw_borders(borders);
+                }
+                dc.draw_vertical_line(*a, YELLOW_COLOR);
+                for point in between {
+                    dc.draw_point(point, RED_COLOR);
+                }
+                dc.draw_path(&[bridge.a, bridge.b], GREEN_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::algos::graham_common::{sort_by_angle, step};
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn bridges_form_the_same_hull_as_graham() {
+        for seed in 0..20 {
+            let mut points = Uniform.generate(60, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<KirkpatrickSeidel>(points.clone());
+            let mut vertices: Vec<Point> = states
+                .last()
+                .unwrap()
+                .hull
+                .iter()
+                .flat_map(|edge| vec![edge.a, edge.b])
+                .collect();
+            vertices.sort_unstable_by(cmp_by_xy);
+            vertices.dedup();
+
+            sort_by_angle(&mut points);
+            let mut hull = Vec::new();
+            while !points.is_empty() {
+                step(&mut points, &mut hull, |a, b, c| rotation(a, b, c) > 0.);
+            }
+            hull.sort_unstable_by(cmp_by_xy);
+            assert_eq!(hull, vertices, "seed {}", seed);
+        }
+    }
+}
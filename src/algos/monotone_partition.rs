@@ -0,0 +1,297 @@
+use crate::algos::{all_states, Algo};
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VertexType {
+    Start,
+    End,
+    Split,
+    Merge,
+    Regular,
+}
+
+// Higher y goes first, equal y is broken by smaller x
+pub fn is_above(a: &Point, b: &Point) -> bool {
+    a.y > b.y || (a.y == b.y && a.x < b.x)
+}
+
+fn vertex_type(polygon: &[Point], i: usize) -> VertexType {
+    let n = polygon.len();
+    let (prev, cur, next) = (
+        &polygon[(i + n - 1) % n],
+        &polygon[i],
+        &polygon[(i + 1) % n],
+    );
+    let convex = rotation(prev, cur, next) > 0.;
+    match (is_above(prev, cur), is_above(next, cur), convex) {
+        (false, false, true) => VertexType::Start,
+        (false, false, false) => VertexType::Split,
+        (true, true, true) => VertexType::End,
+        (true, true, false) => VertexType::Merge,
+        _ => VertexType::Regular,
+    }
+}
+
+// Edge `i` goes from vertex `i` to vertex `i + 1` of the counterclockwise polygon
+#[derive(Copy, Clone, Debug)]
+struct StatusEdge {
+    edge: usize,
+    helper: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    polygon: Vec<Point>,
+    types: Vec<VertexType>,
+    events: Vec<usize>,
+    status: Vec<StatusEdge>,
+    diagonals: Vec<(usize, usize)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Vertex((Point, VertexType, Vec<Pair>)),
+}
+
+impl State {
+    fn edge(&self, i: usize) -> Pair {
+        let n = self.polygon.len();
+        Pair::new(self.polygon[i], self.polygon[(i + 1) % n])
+    }
+
+    fn x_at(&self, i: usize, y: f32) -> f32 {
+        let e = self.edge(i);
+        if eps_equal(e.a.y, e.b.y) {
+            e.a.x.min(e.b.x)
+        } else {
+            e.a.x + (e.b.x - e.a.x) * (y - e.a.y) / (e.b.y - e.a.y)
+        }
+    }
+
+    fn find(&self, edge: usize) -> Option<usize> {
+        self.status.iter().position(|s| s.edge == edge)
+    }
+
+    // Position of the status edge directly to the left of vertex `v`
+    fn left_of(&self, v: usize) -> Option<usize> {
+        let p = self.polygon[v];
+        self.status
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| self.x_at(s.edge, p.y) <= p.x)
+            .max_by(|(_, a), (_, b)| {
+                self.x_at(a.edge, p.y)
+                    .partial_cmp(&self.x_at(b.edge, p.y))
+                    .unwrap()
+            })
+            .map(|(i, _)| i)
+    }
+
+    // Updates the status at vertex `v`, fails if an edge it relies on isn't there
+    fn handle(&mut self, v: usize, added: &mut Vec<Pair>) -> Option<()> {
+        let n = self.polygon.len();
+        let prev_edge = (v + n - 1) % n;
+        match self.types[v] {
+            VertexType::Start => self.status.push(StatusEdge { edge: v, helper: v }),
+            VertexType::End => {
+                let prev = self.find(prev_edge)?;
+                self.connect_merge_helper(prev, v, added);
+                self.status.remove(prev);
+            }
+            VertexType::Split => {
+                let left = self.left_of(v)?;
+                let helper = self.status[left].helper;
+                self.diagonals.push((v, helper));
+                added.push(Pair::new(self.polygon[v], self.polygon[helper]));
+                self.status[left].helper = v;
+                self.status.push(StatusEdge { edge: v, helper: v });
+            }
+            VertexType::Merge => {
+                let prev = self.find(prev_edge)?;
+                self.connect_merge_helper(prev, v, added);
+                self.status.remove(prev);
+                let left = self.left_of(v)?;
+                self.connect_merge_helper(left, v, added);
+                self.status[left].helper = v;
+            }
+            VertexType::Regular => {
+                // going down along the left chain, the interior is to the right of the vertex
+                if is_above(&self.polygon[prev_edge], &self.polygon[v]) {
+                    let prev = self.find(prev_edge)?;
+                    self.connect_merge_helper(prev, v, added);
+                    self.status.remove(prev);
+                    self.status.push(StatusEdge { edge: v, helper: v });
+                } else {
+                    let left = self.left_of(v)?;
+                    self.connect_merge_helper(left, v, added);
+                    self.status[left].helper = v;
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn connect_merge_helper(&mut self, status: usize, v: usize, added: &mut Vec<Pair>) {
+        let helper = self.status[status].helper;
+        if self.types[helper] == VertexType::Merge {
+            self.diagonals.push((v, helper));
+            added.push(Pair::new(self.polygon[v], self.polygon[helper]));
+        }
+    }
+}
+
+// Splits the polygon by non-crossing diagonals between its vertices
+pub fn split_by_diagonals(polygon: &[Point], diagonals: &[(usize, usize)]) -> Vec<Vec<Point>> {
+    let mut pieces = Vec::new();
+    let mut stack = vec![(0..polygon.len()).collect::<Vec<usize>>()];
+    while let Some(piece) = stack.pop() {
+        let split = diagonals.iter().find_map(|(a, b)| {
+            let i = piece.iter().position(|v| v == a)?;
+            let j = piece.iter().position(|v| v == b)?;
+            let (i, j) = (i.min(j), i.max(j));
+            if j - i > 1 && j - i + 1 < piece.len() {
+                Some((i, j))
+            } else {
+                None
+            }
+        });
+        match split {
+            Some((i, j)) => {
+                stack.push(piece[i..=j].to_vec());
+                stack.push(piece[j..].iter().chain(&piece[..=i]).copied().collect());
+            }
+            None => pieces.push(piece.iter().map(|v| polygon[*v]).collect()),
+        }
+    }
+    pieces
+}
+
+// Counterclockwise y-monotone pieces of the polygon
+pub fn monotone_pieces(polygon: Vec<Point>) -> Vec<Vec<Point>> {
+    let (states, _) = all_states::<MonotonePartition>(polygon);
+    let state = states.last().unwrap();
+    split_by_diagonals(&state.polygon, &state.diagonals)
+}
+
+pub struct MonotonePartition;
+
+impl Algo for MonotonePartition {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(mut polygon: Vec<Point>) -> State {
+        if signed_area(&polygon) < 0. {
+            polygon.reverse();
+        }
+        let types = (0..polygon.len())
+            .map(|i| vertex_type(&polygon, i))
+            .collect();
+        let mut events: Vec<usize> = (0..polygon.len()).collect();
+        // popped from the end, so the topmost vertex goes last
+        events.sort_unstable_by(|a, b| {
+            cmp_by_y(&polygon[*a], &polygon[*b]).then_with(|| cmp_by_x(&polygon[*b], &polygon[*a]))
+        });
+        State {
+            polygon,
+            types,
+            events,
+            status: Vec::new(),
+            diagonals: Vec::new(),
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let v = match state.events.pop() {
+            Some(v) => v,
+            None => return (state, Action::NoAction),
+        };
+        let mut added = Vec::new();
+        let vertex_type = state.types[v];
+        if state.handle(v, &mut added).is_none() {
+            // edges of a simple polygon are always found, so it crosses itself and the sweep stops
+            state.events.clear();
+            return (state, Action::NoAction);
+        }
+        let point = state.polygon[v];
+        (state, Action::Vertex((point, vertex_type, added)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.events.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        dc.draw_polygon(&state.polygon, WHITE_COLOR);
+        for s in &state.status {
+            let edge = state.edge(s.edge);
+            dc.draw_line(&edge.a, &edge.b, BLUE_COLOR);
+            dc.draw_point(&state.polygon[s.helper], YELLOW_COLOR);
+        }
+        for (a, b) in &state.diagonals {
+            dc.draw_line(&state.polygon[*a], &state.polygon[*b], GREEN_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Vertex((v, vertex_type, added)) => {
+                dc.draw_line(&Point::new(0., v.y), &Point::new(MAX_X, v.y), BLUE_COLOR);
+                for diagonal in added {
+                    dc.draw_line(&diagonal.a, &diagonal.b, RED_COLOR);
+                }
+                let color = match vertex_type {
+                    VertexType::Start | VertexType::End => YELLOW_COLOR,
+                    VertexType::Split | VertexType::Merge => RED_COLOR,
+                    VertexType::Regular => GREEN_COLOR,
+                };
+                dc.draw_point(v, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::monotone_triangulation::MonotoneTriangulation;
+    use crate::generators::{Distribution, SimplePolygon};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Going around a y-monotone polygon the direction changes only at the top and at the bottom
+    fn is_monotone(piece: &[Point]) -> bool {
+        let n = piece.len();
+        let down = |i: usize| is_above(&piece[i], &piece[(i + 1) % n]);
+        (0..n).filter(|i| down(*i) != down((i + 1) % n)).count() == 2
+    }
+
+    #[test]
+    fn splits_into_monotone_pieces() {
+        // small polygons are the most likely to be degenerate, so they get more seeds
+        for seed in 0..200 {
+            let n = if seed < 50 { 30 } else { 4 + seed as usize % 3 };
+            let polygon = SimplePolygon.generate(n, &mut StdRng::seed_from_u64(seed));
+            let pieces = monotone_pieces(polygon.clone());
+            assert!(pieces.iter().all(|p| is_monotone(p)), "seed {}", seed);
+            let area: f32 = pieces.iter().map(|p| signed_area(p)).sum();
+            assert!((area - signed_area(&polygon)).abs() < 1e-3, "seed {}", seed);
+        }
+
+        // a self-intersecting polygon stops the sweep instead of panicking
+        let bowtie = vec![
+            Point::new(1., 1.),
+            Point::new(5., 5.),
+            Point::new(5., 1.),
+            Point::new(1., 5.),
+            Point::new(3., 7.),
+        ];
+        let (states, _) = all_states::<MonotonePartition>(bowtie.clone());
+        assert!(MonotonePartition::is_final(states.last().unwrap()));
+        let (states, _) = all_states::<MonotoneTriangulation>(bowtie);
+        assert!(MonotoneTriangulation::is_final(states.last().unwrap()));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::algos::monotone_partition::{is_above, monotone_pieces};
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+#[derive(Clone, Debug)]
+struct Piece {
+    polygon: Vec<Point>,
+    // vertices from top to bottom, with `true` for the left chain
+    sorted: Vec<(Point, bool)>,
+    next: usize,
+    stack: Vec<(Point, bool)>,
+}
+
+impl Piece {
+    fn new(polygon: Vec<Point>) -> Self {
+        let n = polygon.len();
+        let top = (0..n)
+            .find(|i| (0..n).all(|j| j == *i || is_above(&polygon[*i], &polygon[j])))
+            .unwrap();
+        let bottom = (0..n)
+            .find(|i| (0..n).all(|j| j == *i || is_above(&polygon[j], &polygon[*i])))
+            .unwrap();
+        // counterclockwise from the top vertex goes down the left chain
+        let mut sorted: Vec<(Point, bool)> = (0..n)
+            .map(|i| {
+                let left = (i + n - top) % n <= (bottom + n - top) % n;
+                (polygon[i], left)
+            })
+            .collect();
+        sorted.sort_unstable_by(|a, b| cmp_by_y(&b.0, &a.0).then_with(|| cmp_by_x(&a.0, &b.0)));
+        let stack = sorted[..2].to_vec();
+        Self {
+            polygon,
+            sorted,
+            next: 2,
+            stack,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    polygon: Vec<Point>,
+    pieces: Vec<Vec<Point>>,
+    piece: Option<Piece>,
+    diagonals: Vec<Pair>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Piece(Vec<Point>),
+    Vertex((Point, Vec<Point>, Vec<Pair>)),
+}
+
+// Diagonal from `u` to `q` over the popped `p` is inside if the chain turns towards the interior at `p`
+fn is_inside(u: &Point, left: bool, p: &Point, q: &Point) -> bool {
+    let rot = rotation(q, p, u);
+    if left {
+        rot > 0.
+    } else {
+        rot < 0.
+    }
+}
+
+pub struct MonotoneTriangulation;
+
+impl Algo for MonotoneTriangulation {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(mut polygon: Vec<Point>) -> State {
+        if signed_area(&polygon) < 0. {
+            polygon.reverse();
+        }
+        let pieces = monotone_pieces(polygon.clone());
+        State {
+            polygon,
+            pieces,
+            piece: None,
+            diagonals: Vec::new(),
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let mut piece = match state.piece.take() {
+            Some(piece) => piece,
+            None => {
+                return match state.pieces.pop() {
+                    Some(polygon) => {
+                        // the diagonals of the partition bound the pieces
+                        for i in 0..polygon.len() {
+                            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                            let n = state.polygon.len();
+                            let is_edge = (0..n).any(|j| {
+                                let edge = Pair::new(state.polygon[j], state.polygon[(j + 1) % n]);
+                                edge == Pair::new(a, b) || edge == Pair::new(b, a)
+                            });
+                            let known = state
+                                .diagonals
+                                .iter()
+                                .any(|d| *d == Pair::new(a, b) || *d == Pair::new(b, a));
+                            if !is_edge && !known {
+                                state.diagonals.push(Pair::new(a, b));
+                            }
+                        }
+                        state.piece = Some(Piece::new(polygon.clone()));
+                        (state, Action::Piece(polygon))
+                    }
+                    None => (state, Action::NoAction),
+                };
+            }
+        };
+
+        let (u, left) = piece.sorted[piece.next];
+        let is_last = piece.next + 1 == piece.sorted.len();
+        let mut added = Vec::new();
+        if is_last {
+            // the bottom vertex sees everything on the stack but its ends
+            let k = piece.stack.len();
+            for (p, _) in &piece.stack[1..k - 1] {
+                added.push(Pair::new(u, *p));
+            }
+            piece.stack.clear();
+        } else if piece.stack.last().unwrap().1 != left {
+            let popped: Vec<(Point, bool)> = piece.stack.drain(..).collect();
+            for (p, _) in &popped[1..] {
+                added.push(Pair::new(u, *p));
+            }
+            piece.stack.push(*popped.last().unwrap());
+            piece.stack.push((u, left));
+        } else {
+            let mut last = piece.stack.pop().unwrap();
+            while let Some(top) = piece.stack.last().copied() {
+                if !is_inside(&u, left, &last.0, &top.0) {
+                    break;
+                }
+                added.push(Pair::new(u, top.0));
+                last = piece.stack.pop().unwrap();
+            }
+            piece.stack.push(last);
+            piece.stack.push((u, left));
+        }
+        piece.next += 1;
+        state.diagonals.extend(added.iter());
+        let stack = piece.stack.iter().map(|(p, _)| *p).collect();
+        if !is_last {
+            state.piece = Some(piece);
+        }
+        (state, Action::Vertex((u, stack, added)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.piece.is_none() && state.pieces.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        dc.draw_polygon(&state.polygon, WHITE_COLOR);
+        for diagonal in &state.diagonals {
+            dc.draw_line(&diagonal.a, &diagonal.b, GREEN_COLOR);
+        }
+        if let Some(piece) = &state.piece {
+            dc.draw_polygon(&piece.polygon, YELLOW_COLOR);
+            let stack: Vec<Point> = piece.stack.iter().map(|(p, _)| *p).collect();
+            dc.draw_path(&stack, BLUE_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Piece(polygon) => dc.draw_polygon(polygon, YELLOW_COLOR),
+            Action::Vertex((u, stack, added)) => {
+                dc.draw_line(&Point::new(0., u.y), &Point::new(MAX_X, u.y), BLUE_COLOR);
+                for diagonal in added {
+                    dc.draw_line(&diagonal.a, &diagonal.b, RED_COLOR);
+                }
+                dc.draw_path(stack, BLUE_COLOR);
+                dc.draw_point(u, RED_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, SimplePolygon};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn crosses(a: &Pair, b: &Pair) -> bool {
+        rotation(&a.a, &a.b, &b.a) * rotation(&a.a, &a.b, &b.b) < 0.
+            && rotation(&b.a, &b.b, &a.a) * rotation(&b.a, &b.b, &a.b) < 0.
+    }
+
+    #[test]
+    fn triangulates_into_n_minus_2_triangles() {
+        // small polygons are the most likely to be degenerate, so they get more seeds
+        for seed in 0..200 {
+            let n = if seed < 50 { 30 } else { 4 + seed as usize % 3 };
+            let polygon = SimplePolygon.generate(n, &mut StdRng::seed_from_u64(seed));
+            let area = signed_area(&polygon);
+            let (states, _) = all_states::<MonotoneTriangulation>(polygon.clone());
+            let diagonals = &states.last().unwrap().diagonals;
+            assert_eq!(polygon.len() - 3, diagonals.len(), "seed {}", seed);
+            let n = polygon.len();
+            let edges = (0..n).map(|i| Pair::new(polygon[i], polygon[(i + 1) % n]));
+            let segments: Vec<Pair> = edges.chain(diagonals.iter().copied()).collect();
+            for d in diagonals {
+                assert!(
+                    segments.iter().all(|s| !crosses(d, s)),
+                    "seed {}: {:?} crosses",
+                    seed,
+                    d
+                );
+            }
+
+            let pieces: f32 = monotone_pieces(polygon)
+                .iter()
+                .map(|p| signed_area(p))
+                .sum();
+            assert!((pieces - area).abs() < 1e-3, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::algos::{Algo, WithParams};
+use crate::canvas::*;
+use crate::common::*;
+
+// Cells this small aren't split even if they hold too many points
+const MIN_CELL: f32 = 1e-3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Params {
+    pub range: Rect,
+    // maximum number of points in a leaf cell
+    pub capacity: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    cell: Rect,
+    // all points of the subtree
+    points: Vec<Point>,
+    children: Option<[usize; 4]>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Phase {
+    Build,
+    Search,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    range: Rect,
+    capacity: usize,
+    nodes: Vec<Node>,
+    build: Vec<usize>,
+    search: Vec<usize>,
+    reported: Vec<Point>,
+    phase: Phase,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Split((Rect, Vec<Point>)),
+    Inside((Rect, Vec<Point>)),
+    Partial((Rect, Vec<Point>)),
+    Reject(Rect),
+}
+
+fn quadrants(cell: &Rect) -> [Rect; 4] {
+    let (lb, rt, c) = (cell.lb, cell.rt, cell.center());
+    [
+        Rect::new(lb, c),
+        Rect::new(Point::new(c.x, lb.y), Point::new(rt.x, c.y)),
+        Rect::new(Point::new(lb.x, c.y), Point::new(c.x, rt.y)),
+        Rect::new(c, rt),
+    ]
+}
+
+pub struct Quadtree;
+
+impl Quadtree {
+    fn needs_split(state: &State, node: &Node) -> bool {
+        node.points.len() > state.capacity && node.cell.rt.x - node.cell.lb.x > MIN_CELL
+    }
+}
+
+impl Algo for Quadtree {
+    type State = State;
+    type Action = Action;
+    type Input = WithParams<Vec<Point>, Params>;
+
+    fn first_state(input: Self::Input) -> State {
+        let WithParams {
+            data: points,
+            params: Params { range, capacity },
+        } = input;
+        let root = Node {
+            cell: Rect::full(),
+            points: points.clone(),
+            children: None,
+        };
+        State {
+            points,
+            range,
+            capacity,
+            nodes: vec![root],
+            build: vec![0],
+            search: Vec::new(),
+            reported: Vec::new(),
+            phase: Phase::Build,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.phase == Phase::Done {
+            return (state, Action::NoAction);
+        }
+        // cells which don't need splitting are skipped without a step
+        while let Some(index) = state.build.pop() {
+            if !Quadtree::needs_split(&state, &state.nodes[index]) {
+                continue;
+            }
+            let node = state.nodes[index].clone();
+            let c = node.cell.center();
+            let mut children = [0; 4];
+            for (i, cell) in quadrants(&node.cell).iter().enumerate() {
+                // points on the dividing lines go to the right and top quadrants
+                let points = node
+                    .points
+                    .iter()
+                    .filter(|p| (p.x >= c.x) == (i % 2 == 1) && (p.y >= c.y) == (i >= 2))
+                    .copied()
+                    .collect();
+                children[i] = state.nodes.len();
+                state.nodes.push(Node {
+                    cell: *cell,
+                    points,
+                    children: None,
+                });
+            }
+            state.nodes[index].children = Some(children);
+            state.build.extend(children.iter().rev());
+            return (state, Action::Split((node.cell, node.points)));
+        }
+        if state.phase == Phase::Build {
+            state.phase = Phase::Search;
+            state.search.push(0);
+        }
+
+        let index = state.search.pop().unwrap();
+        let action = {
+            let node = &state.nodes[index];
+            if !state.range.intersects(&node.cell) {
+                Action::Reject(node.cell)
+            } else if state.range.contains_rect(&node.cell) {
+                // the whole subtree is reported without descending
+                state.reported.extend(node.points.iter());
+                Action::Inside((node.cell, node.points.clone()))
+            } else if let Some(children) = node.children {
+                let cell = node.cell;
+                state.search.extend(children.iter().rev());
+                Action::Partial((cell, Vec::new()))
+            } else {
+                let range = state.range;
+                let found: Vec<Point> = node
+                    .points
+                    .iter()
+                    .filter(|p| range.contains(p))
+                    .copied()
+                    .collect();
+                state.reported.extend(found.iter());
+                Action::Partial((node.cell, found))
+            }
+        };
+        if state.search.is_empty() {
+            state.phase = Phase::Done;
+        }
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.phase == Phase::Done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        if state.phase != Phase::Build {
+            dc.fill_rect(&state.range.lb, &state.range.rt, YELLOW_COLOR);
+        }
+        for node in &state.nodes {
+            dc.draw_rect(&node.cell, BLUE_COLOR);
+        }
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        if state.phase != Phase::Build {
+            dc.draw_rect(&state.range, YELLOW_COLOR);
+            for point in &state.reported {
+                dc.draw_point(point, GREEN_COLOR);
+            }
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Split((cell, points)) => {
+                dc.fill_rect(&cell.lb, &cell.rt, GREEN_COLOR);
+                for point in points {
+                    dc.draw_point(point, YELLOW_COLOR);
+                }
+            }
+            Action::Inside((cell, points)) => {
+                dc.fill_rect(&cell.lb, &cell.rt, GREEN_COLOR);
+                for point in points {
+                    dc.draw_point(point, GREEN_COLOR);
+                }
+            }
+            Action::Partial((cell, points)) => {
+                dc.draw_rect(cell, YELLOW_COLOR);
+                for point in points {
+                    dc.draw_point(point, GREEN_COLOR);
+                }
+            }
+            Action::Reject(cell) => dc.fill_rect(&cell.lb, &cell.rt, RED_COLOR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{random_rect, Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn reports_points_in_range() {
+        for seed in 0..20 {
+            let points = Uniform.generate(60, &mut StdRng::seed_from_u64(seed));
+            let params = Params {
+                range: random_rect(seed),
+                capacity: 1 + seed as usize % 3,
+            };
+            let (states, _) = all_states::<Quadtree>(WithParams::new(points.clone(), params));
+            let state = states.last().unwrap();
+            for node in &state.nodes {
+                assert!(node.children.is_some() || node.points.len() <= params.capacity);
+            }
+            let mut reported = state.reported.clone();
+            let mut expected: Vec<Point> = points
+                .into_iter()
+                .filter(|p| state.range.contains(p))
+                .collect();
+            reported.sort_unstable_by(cmp_by_xy);
+            expected.sort_unstable_by(cmp_by_xy);
+            assert_eq!(expected, reported, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+// Points on the outer (left) side of the chord a -> b
+#[derive(Clone, Debug)]
+struct Chord {
+    a: Point,
+    b: Point,
+    points: Vec<Point>,
+}
+
+impl Chord {
+    fn new(a: Point, b: Point, candidates: &[Point]) -> Self {
+        let points = candidates
+            .iter()
+            .filter(|p| rotation(&a, &b, p) > 0.)
+            .copied()
+            .collect();
+        Self { a, b, points }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum StackState {
+    Extremes,
+    Farthest(Chord),
+    Partition(Chord, Point),
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    hull: Vec<Pair>,
+    stack: Vec<StackState>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Extremes(Pair),
+    Farthest((Pair, Point, Vec<Point>)),
+    Discard((Pair, Point, Vec<Point>)),
+    HullEdge(Pair),
+}
+
+pub struct Quickhull;
+
+impl Algo for Quickhull {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        State {
+            points,
+            hull: Vec::new(),
+            stack: vec![StackState::Extremes],
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.stack.is_empty() {
+            return (state, Action::NoAction);
+        }
+        let action = match state.stack.pop().unwrap() {
+            StackState::Extremes => {
+                let a = *state.points.iter().min_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                let b = *state.points.iter().max_by(|a, b| cmp_by_xy(a, b)).unwrap();
+                state
+                    .stack
+                    .push(StackState::Farthest(Chord::new(b, a, &state.points)));
+                state
+                    .stack
+                    .push(StackState::Farthest(Chord::new(a, b, &state.points)));
+                Action::Extremes(Pair::new(a, b))
+            }
+            StackState::Farthest(chord) => {
+                let pair = Pair::new(chord.a, chord.b);
+                match chord
+                    .points
+                    .iter()
+                    .max_by(|p, q| {
+                        rotation(&chord.a, &chord.b, p)
+                            .partial_cmp(&rotation(&chord.a, &chord.b, q))
+                            .unwrap()
+                    })
+                    .copied()
+                {
+                    Some(c) => {
+                        let points = chord.points.clone();
+                        state.stack.push(StackState::Partition(chord, c));
+                        Action::Farthest((pair, c, points))
+                    }
+                    None => {
+                        state.hull.push(pair);
+                        Action::HullEdge(pair)
+                    }
+                }
+            }
+            StackState::Partition(chord, c) => {
+                let left = Chord::new(chord.a, c, &chord.points);
+                let right = Chord::new(c, chord.b, &chord.points);
+                let discarded = chord
+                    .points
+                    .iter()
+                    .filter(|p| **p != c && !left.points.contains(p) && !right.points.contains(p))
+                    .copied()
+                    .collect();
+                state.stack.push(StackState::Farthest(right));
+                state.stack.push(StackState::Farthest(left));
+                Action::Discard((Pair::new(chord.a, chord.b), c, discarded))
+            }
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.stack.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        for task in &state.stack {
+            if let StackState::Farthest(chord) = task {
+                dc.draw_line(&chord.a, &chord.b, YELLOW_COLOR);
+            }
+        }
+        for edge in &state.hull {
+            dc.draw_line(&edge.a, &edge.b, BLUE_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Extremes(pair) => {
+                dc.draw_path(&[pair.a, pair.b], GREEN_COLOR);
+            }
+            Action::Farthest((chord, c, points)) => {
+                for point in points {
+                    dc.draw_point(point, YELLOW_COLOR);
+                }
+                dc.draw_path(&[chord.a, *c, chord.b], GREEN_COLOR);
+            }
+            Action::Discard((chord, c, discarded)) => {
+                dc.draw_path(&[chord.a, *c, chord.b, chord.a], GREEN_COLOR);
+                for point in discarded {
+                    dc.draw_point(point, RED_COLOR);
+                }
+            }
+            Action::HullEdge(edge) => {
+                dc.draw_path(&[edge.a, edge.b], GREEN_COLOR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::algos::graham_andrew::convex_hull;
+    use crate::generators::{Circle, Distribution, Gaussian, Parabola, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sorted_edges(mut edges: Vec<Pair>) -> Vec<Pair> {
+        for edge in &mut edges {
+            if cmp_by_xy(&edge.b, &edge.a).is_lt() {
+                *edge = Pair::new(edge.b, edge.a);
+            }
+        }
+        edges.sort_by(|p, q| cmp_by_xy(&p.a, &q.a).then(cmp_by_xy(&p.b, &q.b)));
+        edges
+    }
+
+    #[test]
+    fn quickhull_finds_the_same_hull_as_graham() {
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Uniform),
+            Box::new(Circle),
+            Box::new(Gaussian {
+                clusters: 3,
+                sigma: 1.,
+            }),
+            Box::new(Parabola),
+        ];
+        for distribution in distributions {
+            for seed in 0..20 {
+                let points = distribution.generate(60, &mut StdRng::seed_from_u64(seed));
+                let (states, _) = all_states::<Quickhull>(points.clone());
+                let hull = convex_hull(points);
+                let expected = (0..hull.len())
+                    .map(|i| Pair::new(hull[i], hull[(i + 1) % hull.len()]))
+                    .collect();
+                assert_eq!(
+                    sorted_edges(expected),
+                    sorted_edges(states.last().unwrap().hull.clone()),
+                    "seed {}",
+                    seed
+                );
+            }
+        }
+    }
+}
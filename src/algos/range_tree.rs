@@ -0,0 +1,415 @@
+use crate::algos::{Algo, WithParams};
+use crate::canvas::*;
+use crate::common::*;
+
+// Points `l..r` in the x order, leaves hold a single point
+#[derive(Clone, Debug)]
+struct Node {
+    l: usize,
+    r: usize,
+    // the largest point of the left subtree
+    key: Point,
+    // secondary structure, the same points sorted by y
+    ys: Vec<Point>,
+    // fractional cascading: how many points of each child precede `ys[i]`
+    bridges: Vec<[usize; 2]>,
+    children: Option<[usize; 2]>,
+}
+
+#[derive(Clone, Debug)]
+struct Task {
+    l: usize,
+    r: usize,
+    ys: Vec<Point>,
+    parent: Option<(usize, usize)>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Phase {
+    Build,
+    Split,
+    LeftPath,
+    RightPath,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    range: Rect,
+    cascading: bool,
+    nodes: Vec<Node>,
+    build: Vec<Task>,
+    phase: Phase,
+    cur: usize,
+    // number of points below the range in `cur`, followed down with cascading
+    pos: usize,
+    split: usize,
+    split_pos: usize,
+    reported: Vec<Point>,
+}
+
+pub type Canonical = (HorBorders, Vec<Point>);
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Build((HorBorders, Vec<Point>)),
+    Path((HorBorders, Option<Canonical>, Vec<Pair>)),
+    Split(HorBorders),
+    Leaf((Point, bool)),
+}
+
+impl State {
+    fn borders(&self, node: usize) -> HorBorders {
+        let node = &self.nodes[node];
+        HorBorders::new(self.points[node.l].x, self.points[node.r - 1].x)
+    }
+
+    fn below(&self, ys: &[Point]) -> usize {
+        ys.partition_point(|p| p.y < self.range.lb.y)
+    }
+
+    // Position in the child without searching its points, when cascading
+    fn child_pos(&self, node: usize, side: usize) -> usize {
+        let node = &self.nodes[node];
+        let child = &self.nodes[node.children.unwrap()[side]];
+        if self.cascading {
+            node.bridges[self.pos][side]
+        } else {
+            self.below(&child.ys)
+        }
+    }
+
+    // Cascading pointer from the first point above the bottom of the range to the child
+    fn link(&self, node: usize, side: usize) -> Option<Pair> {
+        let node = &self.nodes[node];
+        let child = &self.nodes[node.children.unwrap()[side]];
+        let to = node.bridges[self.pos][side];
+        match (self.cascading, node.ys.get(self.pos), child.ys.get(to)) {
+            (true, Some(a), Some(b)) => Some(Pair::new(*a, *b)),
+            _ => None,
+        }
+    }
+
+    fn descend(&mut self, side: usize) -> Vec<Pair> {
+        let links = self.link(self.cur, side).into_iter().collect();
+        self.pos = self.child_pos(self.cur, side);
+        self.cur = self.nodes[self.cur].children.unwrap()[side];
+        links
+    }
+
+    // Reports the points of a child within the y range of the query
+    fn report(&mut self, side: usize) -> (Canonical, Option<Pair>) {
+        let link = self.link(self.cur, side);
+        let child = self.nodes[self.cur].children.unwrap()[side];
+        let start = self.child_pos(self.cur, side);
+        let top = self.range.rt.y;
+        let found: Vec<Point> = self.nodes[child].ys[start..]
+            .iter()
+            .take_while(|p| p.y <= top)
+            .copied()
+            .collect();
+        self.reported.extend(found.iter());
+        ((self.borders(child), found), link)
+    }
+
+    fn check_leaf(&mut self) -> Action {
+        let p = self.nodes[self.cur].ys[0];
+        let inside = self.range.contains(&p);
+        if inside {
+            self.reported.push(p);
+        }
+        Action::Leaf((p, inside))
+    }
+}
+
+pub struct RangeTree;
+
+impl RangeTree {
+    fn first_state(input: WithParams<Vec<Point>, Rect>, cascading: bool) -> State {
+        let WithParams {
+            data: mut points,
+            params: range,
+        } = input;
+        points.sort_unstable_by(cmp_by_xy);
+        let mut ys = points.clone();
+        ys.sort_unstable_by(|a, b| cmp_by_y(a, b).then_with(|| cmp_by_x(a, b)));
+        // there is nothing to build or search without points
+        let (build, phase) = if points.is_empty() {
+            (Vec::new(), Phase::Done)
+        } else {
+            let task = Task {
+                l: 0,
+                r: points.len(),
+                ys,
+                parent: None,
+            };
+            (vec![task], Phase::Build)
+        };
+        State {
+            points,
+            range,
+            cascading,
+            nodes: Vec::new(),
+            build,
+            phase,
+            cur: 0,
+            pos: 0,
+            split: 0,
+            split_pos: 0,
+            reported: Vec::new(),
+        }
+    }
+
+    fn build_step(state: &mut State) -> Action {
+        let task = state.build.pop().unwrap();
+        let index = state.nodes.len();
+        if let Some((parent, side)) = task.parent {
+            state.nodes[parent].children.as_mut().unwrap()[side] = index;
+        }
+        let m = (task.l + task.r) / 2;
+        let key = state.points[m.max(task.l + 1) - 1];
+        let mut node = Node {
+            l: task.l,
+            r: task.r,
+            key,
+            ys: task.ys,
+            bridges: Vec::new(),
+            children: None,
+        };
+        if task.r - task.l > 1 {
+            // filtering keeps the children sorted by y
+            let is_left = |p: &Point| cmp_by_xy(p, &key) != std::cmp::Ordering::Greater;
+            let (left, right): (Vec<Point>, Vec<Point>) = node.ys.iter().partition(|p| is_left(p));
+            let mut counts = [0, 0];
+            for p in &node.ys {
+                node.bridges.push(counts);
+                counts[if is_left(p) { 0 } else { 1 }] += 1;
+            }
+            node.bridges.push(counts);
+            node.children = Some([0, 0]);
+            state.build.push(Task {
+                l: m,
+                r: task.r,
+                ys: right,
+                parent: Some((index, 1)),
+            });
+            state.build.push(Task {
+                l: task.l,
+                r: m,
+                ys: left,
+                parent: Some((index, 0)),
+            });
+        }
+        let ys = node.ys.clone();
+        state.nodes.push(node);
+        Action::Build((state.borders(index), ys))
+    }
+
+    fn query_step(state: &mut State) -> Action {
+        let (x1, x2) = (state.range.lb.x, state.range.rt.x);
+        let node = state.nodes[state.cur].clone();
+        let borders = state.borders(state.cur);
+        match (state.phase, node.children) {
+            (Phase::Split, Some(_)) if x2 < node.key.x || node.key.x < x1 => {
+                let links = state.descend(if x2 < node.key.x { 0 } else { 1 });
+                Action::Path((borders, None, links))
+            }
+            (Phase::Split, Some(_)) => {
+                state.split = state.cur;
+                state.split_pos = state.pos;
+                state.descend(0);
+                state.phase = Phase::LeftPath;
+                Action::Split(borders)
+            }
+            (Phase::LeftPath, Some(_)) | (Phase::RightPath, Some(_)) => {
+                let left = state.phase == Phase::LeftPath;
+                // the subtree on the inner side of the path is inside of the x range
+                let inner = if left {
+                    x1 <= node.key.x
+                } else {
+                    node.key.x <= x2
+                };
+                let (canonical, mut links) = if inner {
+                    let (canonical, link) = state.report(if left { 1 } else { 0 });
+                    (Some(canonical), link.into_iter().collect())
+                } else {
+                    (None, Vec::new())
+                };
+                let next = if left == inner { 0 } else { 1 };
+                links.extend(state.descend(next));
+                Action::Path((borders, canonical, links))
+            }
+            (Phase::LeftPath, None) => {
+                let action = state.check_leaf();
+                state.cur = state.split;
+                state.pos = state.split_pos;
+                state.descend(1);
+                state.phase = Phase::RightPath;
+                action
+            }
+            (_, None) => {
+                state.phase = Phase::Done;
+                state.check_leaf()
+            }
+            (Phase::Build, _) | (Phase::Done, _) => Action::NoAction,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let action = match state.phase {
+            Phase::Done => Action::NoAction,
+            Phase::Build => {
+                let action = RangeTree::build_step(&mut state);
+                if state.build.is_empty() {
+                    state.phase = Phase::Split;
+                    state.pos = state.below(&state.nodes[0].ys);
+                }
+                action
+            }
+            _ => RangeTree::query_step(&mut state),
+        };
+        (state, action)
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        if state.phase != Phase::Build {
+            dc.fill_rect(&state.range.lb, &state.range.rt, YELLOW_COLOR);
+        }
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        if state.phase != Phase::Build {
+            dc.draw_rect(&state.range, YELLOW_COLOR);
+            for point in &state.reported {
+                dc.draw_point(point, GREEN_COLOR);
+            }
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Build((borders, ys)) => {
+                dc.draw_borders(borders);
+                dc.draw_path(ys, YELLOW_COLOR);
+            }
+            Action::Path((borders, canonical, links)) => {
+                dc.fill_part(borders.l, borders.r, BLUE_COLOR);
+                if let Some((borders, found)) = canonical {
+                    dc.draw_borders(borders);
+                    for point in found {
+                        dc.draw_point(point, GREEN_COLOR);
+                    }
+                }
+                for link in links {
+                    dc.draw_line(&link.a, &link.b, RED_COLOR);
+                }
+            }
+            Action::Split(borders) => dc.fill_part(borders.l, borders.r, RED_COLOR),
+            Action::Leaf((p, inside)) => {
+                dc.draw_point(p, if *inside { GREEN_COLOR } else { RED_COLOR });
+            }
+        }
+    }
+}
+
+impl Algo for RangeTree {
+    type State = State;
+    type Action = Action;
+    type Input = WithParams<Vec<Point>, Rect>;
+
+    fn first_state(input: Self::Input) -> State {
+        RangeTree::first_state(input, false)
+    }
+
+    fn next_state(state: State) -> (State, Action) {
+        RangeTree::next_state(state)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.phase == Phase::Done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        RangeTree::draw_state(dc, state);
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        RangeTree::draw_action(dc, action);
+    }
+}
+
+// The same query where y positions are followed down the tree instead of searched in every node
+pub struct RangeTreeCascading;
+
+impl Algo for RangeTreeCascading {
+    type State = State;
+    type Action = Action;
+    type Input = WithParams<Vec<Point>, Rect>;
+
+    fn first_state(input: Self::Input) -> State {
+        RangeTree::first_state(input, true)
+    }
+
+    fn next_state(state: State) -> (State, Action) {
+        RangeTree::next_state(state)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.phase == Phase::Done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        RangeTree::draw_state(dc, state);
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        RangeTree::draw_action(dc, action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{random_rect, Distribution, Grid, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn check<TAlgo>(points: Vec<Point>, seed: u64)
+    where
+        TAlgo: Algo<State = State, Input = WithParams<Vec<Point>, Rect>>,
+    {
+        let input = WithParams::new(points.clone(), random_rect(seed));
+        let (states, _) = all_states::<TAlgo>(input);
+        let state = states.last().unwrap();
+        let mut reported = state.reported.clone();
+        let mut expected: Vec<Point> = points
+            .into_iter()
+            .filter(|p| state.range.contains(p))
+            .collect();
+        reported.sort_unstable_by(cmp_by_xy);
+        expected.sort_unstable_by(cmp_by_xy);
+        assert_eq!(expected, reported, "seed {}", seed);
+    }
+
+    #[test]
+    fn reports_points_in_range() {
+        for seed in 0..20 {
+            let uniform = Uniform.generate(60, &mut StdRng::seed_from_u64(seed));
+            let grid = Grid.generate(60, &mut StdRng::seed_from_u64(seed));
+            for points in [uniform, grid] {
+                check::<RangeTree>(points.clone(), seed);
+                check::<RangeTreeCascading>(points, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn handles_no_points() {
+        check::<RangeTree>(Vec::new(), 0);
+        check::<RangeTreeCascading>(Vec::new(), 0);
+    }
+}
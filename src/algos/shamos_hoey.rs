@@ -1,56 +1,10 @@
 use crate::algos::Algo;
+use crate::canvas::*;
 use crate::common::*;
-use crate::draw_context::*;
 use std::cmp::Ordering;
 use std::collections::BTreeSet;
 use std::ops::Bound;
 
-#[derive(Copy, Clone, Debug)]
-pub struct Segment {
-    a: Point,
-    b: Point,
-}
-
-impl Segment {
-    fn new(mut a: Point, mut b: Point) -> Segment {
-        if a.x > b.x {
-            std::mem::swap(&mut a, &mut b);
-        }
-        Segment { a, b }
-    }
-    fn get_y(&self, x: f32) -> f32 {
-        if eps_equal(self.a.x, self.b.x) {
-            self.a.y
-        } else {
-            self.a.y + (self.b.y - self.a.y) * (x - self.a.x) / (self.b.x - self.a.x)
-        }
-    }
-}
-
-impl Ord for Segment {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let x = self.a.x.max(other.a.x);
-        self.get_y(x)
-            .partial_cmp(&other.get_y(x))
-            .unwrap()
-            .then_with(|| self.a.x.partial_cmp(&other.a.x).unwrap())
-            .then_with(|| self.a.y.partial_cmp(&other.a.y).unwrap())
-            .then_with(|| self.b.x.partial_cmp(&other.b.x).unwrap())
-            .then_with(|| self.b.y.partial_cmp(&other.b.y).unwrap())
-    }
-}
-impl PartialOrd for Segment {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl PartialEq for Segment {
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other) == Ordering::Equal
-    }
-}
-impl Eq for Segment {}
-
 #[derive(Copy, Clone, Debug)]
 pub struct Event {
     x: f32,
@@ -94,24 +48,7 @@ pub enum Action {
     Scan((Segment, Option<(Segment, Segment)>, f32)),
 }
 
-fn intersect_1d(mut a1: f32, mut a2: f32, mut b1: f32, mut b2: f32) -> bool {
-    if a1 > a2 {
-        std::mem::swap(&mut a1, &mut a2)
-    }
-    if b1 > b2 {
-        std::mem::swap(&mut b1, &mut b2)
-    }
-    a1.max(b1) <= a2.min(b2)
-}
-
-fn intersect_seg(first: &Segment, second: &Segment) -> bool {
-    intersect_1d(first.a.x, first.b.x, second.a.x, second.b.x)
-        && intersect_1d(first.a.y, first.b.y, second.a.y, second.b.y)
-        && rotation(&first.a, &first.b, &second.a) * rotation(&first.a, &first.b, &second.b) <= 0.
-        && rotation(&second.a, &second.b, &first.a) * rotation(&second.a, &second.b, &first.b) <= 0.
-}
-
-fn neighbors<'a>(
+pub fn neighbors<'a>(
     segments: &'a BTreeSet<Segment>,
     v: &Segment,
 ) -> (Option<&'a Segment>, Option<&'a Segment>) {
@@ -121,25 +58,31 @@ fn neighbors<'a>(
     (before.next_back(), after.next())
 }
 
+// Pairs up points close by x, so that segments are short and some of them intersect
+pub fn make_segments(mut points: Vec<Point>) -> Vec<Segment> {
+    points.sort_unstable_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    for i in (0..points.len()).step_by(2) {
+        let swap_index = (i + 3).min(points.len() - 1);
+        points.swap(i, swap_index);
+    }
+
+    points
+        .iter()
+        .step_by(2)
+        .zip(points.iter().skip(1).step_by(2))
+        .map(|(a, b)| Segment::new(*a, *b))
+        .collect()
+}
+
 pub struct ShamosHoey;
 
 impl Algo for ShamosHoey {
     type State = State;
     type Action = Action;
+    type Input = Vec<Point>;
 
-    fn first_state(mut points: Vec<Point>) -> State {
-        points.sort_unstable_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
-        for i in (0..points.len()).step_by(2) {
-            let swap_index = (i + 3).min(points.len() - 1);
-            points.swap(i, swap_index);
-        }
-
-        let segments: Vec<_> = points
-            .iter()
-            .step_by(2)
-            .zip(points.iter().skip(1).step_by(2))
-            .map(|(a, b)| Segment::new(*a, *b))
-            .collect();
+    fn first_state(points: Vec<Point>) -> State {
+        let segments = make_segments(points);
 
         let mut events: Vec<_> = segments
             .iter()
@@ -192,10 +135,10 @@ impl Algo for ShamosHoey {
     }
 
     fn is_final(state: &State) -> bool {
-        state.result.is_some()
+        state.result.is_some() || state.events.is_empty()
     }
 
-    fn draw_state(dc: &mut DrawContext, state: &State) {
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
         for seg in &state.segments {
             dc.draw_line(&seg.a, &seg.b, WHITE_COLOR);
         }
@@ -208,13 +151,13 @@ impl Algo for ShamosHoey {
         }
     }
 
-    fn draw_action(dc: &mut DrawContext, action: &Action) {
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
         match action {
             Action::NoAction => {}
             Action::Scan((cur_seg, found, x)) => {
                 dc.draw_vertical_line(*x, BLUE_COLOR);
                 dc.draw_line(&cur_seg.a, &cur_seg.b, YELLOW_COLOR);
-                for (seg1, seg2) in found {
+                if let Some((seg1, seg2)) = found {
                     dc.draw_line(&seg1.a, &seg1.b, RED_COLOR);
                     dc.draw_line(&seg2.a, &seg2.b, RED_COLOR);
                 }
@@ -222,3 +165,44 @@ impl Algo for ShamosHoey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{Distribution, Grid, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Grid points share x, so segments start and end at the same sweep positions
+    #[test]
+    fn finds_intersection_like_brute_force() {
+        for seed in 0..20 {
+            let uniform = Uniform.generate(8, &mut StdRng::seed_from_u64(seed));
+            let grid = Grid.generate(8, &mut StdRng::seed_from_u64(seed));
+            for points in [uniform, grid] {
+                let segments = make_segments(points.clone());
+                let expected = (0..segments.len())
+                    .any(|i| (0..i).any(|j| intersect_seg(&segments[i], &segments[j])));
+                let (states, _) = all_states::<ShamosHoey>(points);
+                let result = states.last().unwrap().result;
+                assert_eq!(expected, result.is_some(), "seed {}", seed);
+                if let Some((a, b)) = result {
+                    assert!(intersect_seg(&a, &b), "seed {}", seed);
+                }
+            }
+        }
+
+        // (3, 3) - (7, 1) starts on (1, 1) - (5, 5), parallel (3, 4) - (7, 8) doesn't touch it
+        for (p1, p3, expected) in [((3., 3.), (7., 1.), true), ((3., 4.), (7., 8.), false)] {
+            let points = vec![
+                Point::new(1., 1.),
+                Point::new(p1.0, p1.1),
+                Point::new(5., 5.),
+                Point::new(p3.0, p3.1),
+            ];
+            let (states, _) = all_states::<ShamosHoey>(points);
+            assert_eq!(expected, states.last().unwrap().result.is_some());
+        }
+    }
+}
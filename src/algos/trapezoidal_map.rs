@@ -0,0 +1,502 @@
+use crate::algos::shamos_hoey::make_segments;
+use crate::algos::{Algo, WithParams};
+use crate::canvas::*;
+use crate::common::*;
+
+// The bounding box is a bit larger than the area of the points
+const MARGIN: f32 = 0.5;
+// A point closer than this to a segment touches it
+const TOLERANCE: f32 = 1e-4;
+
+fn touches(s: &Segment, p: &Point) -> bool {
+    Line::new(s.a, s.b).distance(p).abs() < TOLERANCE
+        && s.a.x.min(s.b.x) - TOLERANCE < p.x
+        && p.x < s.a.x.max(s.b.x) + TOLERANCE
+        && s.a.y.min(s.b.y) - TOLERANCE < p.y
+        && p.y < s.a.y.max(s.b.y) + TOLERANCE
+}
+
+// Points paired up like in `shamos_hoey`, without the segments crossing or touching earlier ones,
+// which `intersect_seg` can miss when rounding hides an end lying on the other segment
+pub fn random_segments(points: Vec<Point>) -> Vec<Segment> {
+    let mut kept: Vec<Segment> = Vec::new();
+    for s in make_segments(points) {
+        let apart = |k: &Segment| {
+            !intersect_seg(k, &s)
+                && !touches(k, &s.a)
+                && !touches(k, &s.b)
+                && !touches(&s, &k.a)
+                && !touches(&s, &k.b)
+        };
+        if kept.iter().all(apart) {
+            kept.push(s);
+        }
+    }
+    kept
+}
+
+// Bounded by two segments and by vertical walls through the `left` and `right` points
+#[derive(Copy, Clone, Debug)]
+struct Trapezoid {
+    top: usize,
+    bottom: usize,
+    left: Point,
+    right: Point,
+    // neighbors across the left and the right walls, the lower one goes first,
+    // a single neighbor is kept twice
+    left_links: [Option<usize>; 2],
+    right_links: [Option<usize>; 2],
+    // the leaf of the search structure
+    node: usize,
+    alive: bool,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Node {
+    // left and right of the point
+    X(Point, usize, usize),
+    // above and below the segment
+    Y(usize, usize, usize),
+    Leaf(usize),
+}
+
+#[derive(Clone, Debug)]
+enum Phase {
+    Locate,
+    Split(Vec<usize>),
+    Query(usize),
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    // the bottom and the top of the bounding box go first
+    segments: Vec<Segment>,
+    inserted: usize,
+    traps: Vec<Trapezoid>,
+    dag: Vec<Node>,
+    query: Point,
+    phase: Phase,
+}
+
+pub type Corners = [Point; 4];
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Crossed((Segment, Vec<Corners>)),
+    Split((Segment, Vec<Corners>)),
+    XNode(Point),
+    YNode(Segment),
+    Found(Corners),
+}
+
+fn is_above(s: &Segment, p: &Point) -> bool {
+    rotation(&s.a, &s.b, p) > 0.
+}
+
+impl State {
+    fn corners(&self, t: usize) -> Corners {
+        let t = &self.traps[t];
+        let (top, bottom) = (&self.segments[t.top], &self.segments[t.bottom]);
+        let (l, r) = (t.left.x, t.right.x);
+        [
+            Point::new(l, bottom.get_y(l)),
+            Point::new(r, bottom.get_y(r)),
+            Point::new(r, top.get_y(r)),
+            Point::new(l, top.get_y(l)),
+        ]
+    }
+
+    fn add(&mut self, top: usize, bottom: usize, left: Point, right: Point) -> usize {
+        let index = self.traps.len();
+        self.traps.push(Trapezoid {
+            top,
+            bottom,
+            left,
+            right,
+            left_links: [None; 2],
+            right_links: [None; 2],
+            node: self.dag.len(),
+            alive: true,
+        });
+        self.dag.push(Node::Leaf(index));
+        index
+    }
+
+    fn push_node(&mut self, node: Node) -> usize {
+        self.dag.push(node);
+        self.dag.len() - 1
+    }
+
+    fn child(&self, node: usize, p: &Point) -> Option<usize> {
+        match self.dag[node] {
+            Node::X(point, left, right) => match cmp_by_xy(p, &point) {
+                std::cmp::Ordering::Less => Some(left),
+                _ => Some(right),
+            },
+            Node::Y(segment, above, below) => {
+                let seg = &self.segments[segment];
+                Some(if is_above(seg, p) { above } else { below })
+            }
+            Node::Leaf(_) => None,
+        }
+    }
+
+    fn locate(&self, p: &Point) -> usize {
+        let mut node = 0;
+        while let Some(next) = self.child(node, p) {
+            node = next;
+        }
+        match self.dag[node] {
+            Node::Leaf(t) => t,
+            _ => unreachable!(),
+        }
+    }
+
+    // Trapezoids crossed by the segment from left to right
+    fn crossed(&self, s: &Segment) -> Vec<usize> {
+        let mut res = vec![self.locate(&s.a)];
+        loop {
+            let t = &self.traps[*res.last().unwrap()];
+            if cmp_by_xy(&s.b, &t.right) != std::cmp::Ordering::Greater {
+                return res;
+            }
+            // if a segment starts at the wall point, `s` passes on one side of it
+            let below = is_above(s, &t.right);
+            res.push(t.right_links[if below { 0 } else { 1 }].unwrap());
+        }
+    }
+
+    // Whether the trapezoid is only below or only above the point of its wall
+    fn sides(&self, t: usize, w: &Point) -> (bool, bool) {
+        let t = &self.traps[t];
+        let ends_at = |s: usize| self.segments[s].a == *w || self.segments[s].b == *w;
+        (ends_at(t.top), ends_at(t.bottom))
+    }
+
+    // Trapezoids on both sides of the wall through `w`, reached from `start` by the links
+    fn wall(&self, w: &Point, start: usize) -> Vec<usize> {
+        let mut res = vec![start];
+        let mut i = 0;
+        while i < res.len() {
+            let t = &self.traps[res[i]];
+            let links = if t.right == *w {
+                t.right_links
+            } else {
+                t.left_links
+            };
+            for next in links.iter().flatten() {
+                if !res.contains(next) {
+                    res.push(*next);
+                }
+            }
+            i += 1;
+        }
+        res
+    }
+
+    // Neighbors of `t` among the trapezoids on the other side of the wall through `w`
+    fn neighbors(&self, t: usize, others: &[usize], w: &Point) -> [Option<usize>; 2] {
+        let (below, above) = self.sides(t, w);
+        let mut res: Vec<usize> = others
+            .iter()
+            .filter(|o| {
+                let (o_below, o_above) = self.sides(**o, w);
+                // trapezoids on different sides of the point only touch at it
+                !(below && o_above || above && o_below)
+            })
+            .copied()
+            .collect();
+        res.sort_by_key(|o| !self.sides(*o, w).0);
+        [res.first().copied(), res.last().copied()]
+    }
+
+    fn link(&mut self, w: &Point, wall: &[usize]) {
+        let (left, right): (Vec<usize>, Vec<usize>) =
+            wall.iter().partition(|t| self.traps[**t].right == *w);
+        for t in &left {
+            self.traps[*t].right_links = self.neighbors(*t, &right, w);
+        }
+        for t in &right {
+            self.traps[*t].left_links = self.neighbors(*t, &left, w);
+        }
+    }
+
+    // Replaces the crossed trapezoids, the parts above and below the segment
+    // merge across walls whose points are on the other side of it
+    fn insert(&mut self, si: usize, crossed: &[usize]) -> Vec<usize> {
+        let s = self.segments[si];
+        let (first, last) = (self.traps[crossed[0]], self.traps[*crossed.last().unwrap()]);
+        // walls touching the crossed trapezoids, their links are rebuilt afterwards
+        let mut walls = vec![(first.left, self.wall(&first.left, crossed[0]))];
+        for d in crossed {
+            let w = self.traps[*d].right;
+            walls.push((w, self.wall(&w, *d)));
+        }
+        walls.push((s.a, Vec::new()));
+        walls.push((s.b, Vec::new()));
+        let mut new = Vec::new();
+        let left = if first.left != s.a {
+            Some(self.add(first.top, first.bottom, first.left, s.a))
+        } else {
+            None
+        };
+        let right = if last.right != s.b {
+            Some(self.add(last.top, last.bottom, s.b, last.right))
+        } else {
+            None
+        };
+        let mut up = self.add(first.top, si, s.a, s.b);
+        let mut low = self.add(si, first.bottom, s.a, s.b);
+        new.extend(left.iter().chain(right.iter()));
+        new.extend(&[up, low]);
+
+        for (j, d) in crossed.iter().enumerate() {
+            let old = self.traps[*d];
+            if j > 0 {
+                let wall = self.traps[crossed[j - 1]].right;
+                if is_above(&s, &wall) {
+                    self.traps[up].right = wall;
+                    up = self.add(old.top, si, wall, s.b);
+                    new.push(up);
+                } else {
+                    self.traps[low].right = wall;
+                    low = self.add(si, old.bottom, wall, s.b);
+                    new.push(low);
+                }
+            }
+            self.traps[*d].alive = false;
+            let mut node = Node::Y(si, self.traps[up].node, self.traps[low].node);
+            if j + 1 == crossed.len() {
+                if let Some(right) = right {
+                    let y = self.push_node(node);
+                    node = Node::X(s.b, y, self.traps[right].node);
+                }
+            }
+            if j == 0 {
+                if let Some(left) = left {
+                    let inner = self.push_node(node);
+                    node = Node::X(s.a, self.traps[left].node, inner);
+                }
+            }
+            // the leaf is reused, so the nodes pointing to it see the new structure
+            self.dag[old.node] = node;
+        }
+
+        for (w, mut wall) in walls {
+            wall.retain(|t| self.traps[*t].alive);
+            wall.extend(
+                new.iter()
+                    .filter(|t| self.traps[**t].left == w || self.traps[**t].right == w),
+            );
+            self.link(&w, &wall);
+        }
+        new
+    }
+}
+
+pub struct TrapezoidalMap;
+
+impl Algo for TrapezoidalMap {
+    type State = State;
+    type Action = Action;
+    type Input = WithParams<Vec<Segment>, Point>;
+
+    fn first_state(input: Self::Input) -> State {
+        let WithParams {
+            data: inserted,
+            params: query,
+        } = input;
+        let (lo, hi) = (-MARGIN, MAX_X.max(MAX_Y) + MARGIN);
+        let mut segments = vec![
+            Segment::new(Point::new(lo, lo), Point::new(hi, lo)),
+            Segment::new(Point::new(lo, hi), Point::new(hi, hi)),
+        ];
+        segments.extend(inserted);
+        let mut state = State {
+            segments,
+            inserted: 2,
+            traps: Vec::new(),
+            dag: Vec::new(),
+            query,
+            phase: Phase::Locate,
+        };
+        state.add(1, 0, Point::new(lo, lo), Point::new(hi, hi));
+        if state.segments.len() == 2 {
+            state.phase = Phase::Query(0);
+        }
+        state
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let phase = std::mem::replace(&mut state.phase, Phase::Done);
+        let action = match phase {
+            Phase::Locate => {
+                let s = state.segments[state.inserted];
+                let crossed = state.crossed(&s);
+                let corners = crossed.iter().map(|t| state.corners(*t)).collect();
+                state.phase = Phase::Split(crossed);
+                Action::Crossed((s, corners))
+            }
+            Phase::Split(crossed) => {
+                let si = state.inserted;
+                let new = state.insert(si, &crossed);
+                state.inserted += 1;
+                state.phase = if state.inserted < state.segments.len() {
+                    Phase::Locate
+                } else {
+                    Phase::Query(0)
+                };
+                let corners = new.iter().map(|t| state.corners(*t)).collect();
+                Action::Split((state.segments[si], corners))
+            }
+            Phase::Query(node) => {
+                if let Some(next) = state.child(node, &state.query) {
+                    state.phase = Phase::Query(next);
+                }
+                match state.dag[node] {
+                    Node::X(point, _, _) => Action::XNode(point),
+                    Node::Y(segment, _, _) => Action::YNode(state.segments[segment]),
+                    Node::Leaf(t) => Action::Found(state.corners(t)),
+                }
+            }
+            Phase::Done => Action::NoAction,
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        matches!(state.phase, Phase::Done)
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for t in (0..state.traps.len()).filter(|t| state.traps[*t].alive) {
+            draw_trapezoid(dc, &state.corners(t), BLUE_COLOR);
+        }
+        for (i, s) in state.segments.iter().enumerate().skip(2) {
+            let color = if i < state.inserted {
+                WHITE_COLOR
+            } else {
+                GRAY_COLOR
+            };
+            dc.draw_path(&[s.a, s.b], color);
+        }
+        if let Phase::Query(_) | Phase::Done = state.phase {
+            dc.draw_point(&state.query, YELLOW_COLOR);
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Crossed((s, crossed)) => {
+                for corners in crossed {
+                    draw_trapezoid(dc, corners, RED_COLOR);
+                }
+                dc.draw_path(&[s.a, s.b], YELLOW_COLOR);
+            }
+            Action::Split((s, new)) => {
+                for corners in new {
+                    draw_trapezoid(dc, corners, GREEN_COLOR);
+                }
+                dc.draw_path(&[s.a, s.b], YELLOW_COLOR);
+            }
+            Action::XNode(p) => {
+                dc.draw_vertical_line(p.x, YELLOW_COLOR);
+                dc.draw_point(p, RED_COLOR);
+            }
+            Action::YNode(s) => dc.draw_path(&[s.a, s.b], YELLOW_COLOR),
+            Action::Found(corners) => draw_trapezoid(dc, corners, GREEN_COLOR),
+        }
+    }
+}
+
+fn draw_trapezoid(dc: &mut dyn Canvas, corners: &Corners, color: raqote::SolidSource) {
+    dc.draw_polyline(
+        &[corners[0], corners[1], corners[2], corners[3], corners[0]],
+        color,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{insertion_order, random_point, Distribution, Grid, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Points with the same x are ordered by y, as if the plane were slightly sheared
+    fn shear(p: &Point) -> Point {
+        Point::new(p.x + 1e-3 * p.y, p.y)
+    }
+
+    // The bottom and the top of the trapezoid on the wall through `w`
+    fn span(state: &State, t: usize, w: &Point) -> (f32, f32) {
+        let x = shear(w).x;
+        let y = |s: usize| {
+            let s = &state.segments[s];
+            Segment::new(shear(&s.a), shear(&s.b)).get_y(x)
+        };
+        (y(state.traps[t].bottom), y(state.traps[t].top))
+    }
+
+    fn check(points: Vec<Point>, seed: u64) {
+        let segments = insertion_order(random_segments(points), seed);
+        let n = segments.len();
+        let input = WithParams::new(segments, random_point(seed));
+        let (states, _) = all_states::<TrapezoidalMap>(input);
+        let state = states.last().unwrap();
+        let alive: Vec<usize> = (0..state.traps.len())
+            .filter(|t| state.traps[*t].alive)
+            .collect();
+        assert_eq!(3 * n + 1, alive.len(), "seed {}", seed);
+        // neighbors share the wall and overlap on it by more than a point
+        for t in &alive {
+            let w = state.traps[*t].right;
+            let (bottom, top) = span(state, *t, &w);
+            let mut expected: Vec<usize> = alive
+                .iter()
+                .filter(|u| {
+                    let (u_bottom, u_top) = span(state, **u, &w);
+                    state.traps[**u].left == w && top.min(u_top) - bottom.max(u_bottom) > 1e-4
+                })
+                .copied()
+                .collect();
+            let mut links: Vec<usize> = state.traps[*t]
+                .right_links
+                .iter()
+                .flatten()
+                .copied()
+                .collect();
+            links.dedup();
+            expected.sort_unstable();
+            links.sort_unstable();
+            assert_eq!(expected, links, "seed {}", seed);
+            for u in &expected {
+                assert!(
+                    state.traps[*u].left_links.contains(&Some(*t)),
+                    "seed {}",
+                    seed
+                );
+            }
+        }
+
+        for p in Uniform.generate(50, &mut StdRng::seed_from_u64(!seed)) {
+            let t = &state.traps[state.locate(&p)];
+            assert!(t.alive, "seed {}", seed);
+            assert!(t.left.x <= p.x && p.x <= t.right.x, "seed {}", seed);
+            assert!(!is_above(&state.segments[t.top], &p), "seed {}", seed);
+            assert!(is_above(&state.segments[t.bottom], &p), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn locates_points_in_trapezoids() {
+        for seed in 0..20 {
+            check(Uniform.generate(40, &mut StdRng::seed_from_u64(seed)), seed);
+            check(Grid.generate(40, &mut StdRng::seed_from_u64(seed)), seed);
+        }
+    }
+}
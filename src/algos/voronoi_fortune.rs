@@ -0,0 +1,402 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+// The sweep line moves down, so sites are processed by decreasing y
+#[derive(Copy, Clone, Debug)]
+struct Arc {
+    id: usize,
+    site: Point,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CircleEvent {
+    y: f32,
+    center: Point,
+    r: f32,
+    arc: usize,
+}
+
+// Traced by the breakpoint between the arcs of `left` and `right` until it ends in a vertex
+#[derive(Copy, Clone, Debug)]
+struct Edge {
+    start: Point,
+    end: Option<Point>,
+    left: Point,
+    right: Point,
+}
+
+impl Edge {
+    fn new(start: Point, left: Point, right: Point) -> Self {
+        Self {
+            start,
+            end: None,
+            left,
+            right,
+        }
+    }
+
+    fn direction(&self) -> euclid::Vector2D<f32, f32> {
+        euclid::vec2(self.right.y - self.left.y, self.left.x - self.right.x).normalize()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    sites: Vec<Point>,
+    circles: Vec<CircleEvent>,
+    arcs: Vec<Arc>,
+    // `breakpoints[i]` is the edge traced between `arcs[i]` and `arcs[i + 1]`
+    breakpoints: Vec<usize>,
+    edges: Vec<Edge>,
+    vertices: Vec<Point>,
+    sweep: f32,
+    next_id: usize,
+    done: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Site((Point, Point)),
+    Circle((Point, f32, Point)),
+    Clip(Vec<Pair>),
+}
+
+// Arcs of sites this close to the sweep line are treated as vertical rays
+const ON_SWEEP: f32 = 1e-4;
+
+fn on_sweep(site: &Point, sweep: f32) -> bool {
+    site.y - sweep < ON_SWEEP
+}
+
+fn parabola_y(site: &Point, sweep: f32, x: f32) -> f32 {
+    ((x - site.x).powi(2) + site.y.powi(2) - sweep.powi(2)) / (2. * (site.y - sweep))
+}
+
+// X of the breakpoint which has the arc of `left` to the left of it
+fn breakpoint(left: &Point, right: &Point, sweep: f32) -> f32 {
+    if eps_equal(left.y, right.y) {
+        return (left.x + right.x) / 2.;
+    }
+    if on_sweep(left, sweep) {
+        return left.x;
+    }
+    if on_sweep(right, sweep) {
+        return right.x;
+    }
+    let (x1, y1, x2, y2, l) = (
+        f64::from(left.x),
+        f64::from(left.y),
+        f64::from(right.x),
+        f64::from(right.y),
+        f64::from(sweep),
+    );
+    let (d1, d2) = (0.5 / (y1 - l), 0.5 / (y2 - l));
+    let a = d1 - d2;
+    let b = -2. * (x1 * d1 - x2 * d2);
+    let c = (x1 * x1 + y1 * y1 - l * l) * d1 - (x2 * x2 + y2 * y2 - l * l) * d2;
+    ((-b + (b * b - 4. * a * c).max(0.).sqrt()) / (2. * a)) as f32
+}
+
+impl State {
+    fn breakpoint_x(&self, i: usize) -> f32 {
+        breakpoint(&self.arcs[i].site, &self.arcs[i + 1].site, self.sweep)
+    }
+
+    fn new_arc(&mut self, site: Point) -> Arc {
+        self.next_id += 1;
+        Arc {
+            id: self.next_id,
+            site,
+        }
+    }
+
+    fn forget_circle(&mut self, i: usize) {
+        let id = self.arcs[i].id;
+        self.circles.retain(|c| c.arc != id);
+    }
+
+    // The middle arc vanishes if the breakpoints around it converge
+    fn check_circle(&mut self, i: usize) {
+        if i == 0 || i + 1 >= self.arcs.len() {
+            return;
+        }
+        let (a, b, c) = (
+            self.arcs[i - 1].site,
+            self.arcs[i].site,
+            self.arcs[i + 1].site,
+        );
+        if a == c || rotation(&a, &b, &c) >= 0. {
+            return;
+        }
+        if let Some((center, r)) = circumcircle(&a, &b, &c) {
+            let y = center.y - r;
+            if y <= self.sweep + 1e-4 {
+                self.circles.push(CircleEvent {
+                    y,
+                    center,
+                    r,
+                    arc: self.arcs[i].id,
+                });
+            }
+        }
+    }
+
+    fn site_event(&mut self, p: Point) -> Action {
+        if self.arcs.is_empty() {
+            let arc = self.new_arc(p);
+            self.arcs.push(arc);
+            return Action::Site((p, Point::new(p.x, MAX_Y)));
+        }
+        let i = (0..self.arcs.len() - 1)
+            .find(|i| p.x < self.breakpoint_x(*i))
+            .unwrap_or(self.arcs.len() - 1);
+        let above = self.arcs[i].site;
+        if eps_equal(above.y, p.y) {
+            // Only sites of the topmost row can be met here, their arcs are still vertical rays
+            let start = Point::new((above.x + p.x) / 2., 3. * MAX_Y);
+            let edge = self.edges.len();
+            self.edges.push(Edge::new(start, above, p));
+            let arc = self.new_arc(p);
+            self.arcs.insert(i + 1, arc);
+            self.breakpoints.insert(i, edge);
+            return Action::Site((p, Point::new(p.x, MAX_Y)));
+        }
+
+        self.forget_circle(i);
+        let start = Point::new(p.x, parabola_y(&above, self.sweep, p.x));
+        let (left_edge, right_edge) = (self.edges.len(), self.edges.len() + 1);
+        self.edges.push(Edge::new(start, above, p));
+        self.edges.push(Edge::new(start, p, above));
+        let (middle, right) = (self.new_arc(p), self.new_arc(above));
+        self.arcs.insert(i + 1, middle);
+        self.arcs.insert(i + 2, right);
+        self.breakpoints.insert(i, right_edge);
+        self.breakpoints.insert(i, left_edge);
+        self.check_circle(i);
+        self.check_circle(i + 2);
+        Action::Site((p, start))
+    }
+
+    fn circle_event(&mut self, event: CircleEvent) -> Action {
+        let i = self.arcs.iter().position(|a| a.id == event.arc).unwrap();
+        let site = self.arcs[i].site;
+        self.forget_circle(i - 1);
+        self.forget_circle(i + 1);
+        self.edges[self.breakpoints[i - 1]].end = Some(event.center);
+        self.edges[self.breakpoints[i]].end = Some(event.center);
+        self.vertices.push(event.center);
+
+        let edge = self.edges.len();
+        self.edges.push(Edge::new(
+            event.center,
+            self.arcs[i - 1].site,
+            self.arcs[i + 1].site,
+        ));
+        self.arcs.remove(i);
+        self.breakpoints.remove(i);
+        self.breakpoints[i - 1] = edge;
+        self.check_circle(i - 1);
+        self.check_circle(i);
+        Action::Circle((event.center, event.r, site))
+    }
+
+    fn clip(&mut self) -> Action {
+        let far = 4. * MAX_X.max(MAX_Y);
+        self.edges = self
+            .edges
+            .iter()
+            .filter_map(|e| {
+                let end = e.end.unwrap_or_else(|| e.start + e.direction() * far);
+                clip_to_box(&e.start, &end).map(|clipped| Edge {
+                    start: clipped.a,
+                    end: Some(clipped.b),
+                    ..*e
+                })
+            })
+            .collect();
+        self.arcs.clear();
+        self.breakpoints.clear();
+        self.done = true;
+        Action::Clip(
+            self.edges
+                .iter()
+                .map(|e| Pair::new(e.start, e.end.unwrap()))
+                .collect(),
+        )
+    }
+}
+
+pub struct VoronoiFortune;
+
+impl Algo for VoronoiFortune {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let mut sites = points.clone();
+        sites.sort_unstable_by(|a, b| cmp_by_y(a, b).then_with(|| cmp_by_x(b, a)));
+        State {
+            points,
+            sites,
+            circles: Vec::new(),
+            arcs: Vec::new(),
+            breakpoints: Vec::new(),
+            edges: Vec::new(),
+            vertices: Vec::new(),
+            sweep: MAX_Y,
+            next_id: 0,
+            done: false,
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        if state.done {
+            return (state, Action::NoAction);
+        }
+        let circle = state
+            .circles
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.y.partial_cmp(&b.y).unwrap())
+            .map(|(i, c)| (i, c.y));
+        let site_y = state.sites.last().map(|p| p.y);
+        let action = match (circle, site_y) {
+            (Some((i, y)), None) => {
+                let event = state.circles.remove(i);
+                state.sweep = y;
+                state.circle_event(event)
+            }
+            (Some((i, y)), Some(site_y)) if y >= site_y => {
+                let event = state.circles.remove(i);
+                state.sweep = y;
+                state.circle_event(event)
+            }
+            (_, Some(y)) => {
+                let p = state.sites.pop().unwrap();
+                state.sweep = y;
+                state.site_event(p)
+            }
+            (None, None) => state.clip(),
+        };
+        (state, action)
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.done
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        for point in &state.points {
+            dc.draw_point(point, WHITE_COLOR);
+        }
+        for circle in &state.circles {
+            dc.draw_circle(&circle.center, circle.r, GRAY_COLOR);
+        }
+        for edge in &state.edges {
+            if let Some(end) = &edge.end {
+                dc.draw_line(&edge.start, end, BLUE_COLOR);
+            }
+        }
+        for (i, edge) in state.breakpoints.iter().enumerate() {
+            let edge = &state.edges[*edge];
+            let x = state.breakpoint_x(i);
+            let y = if on_sweep(&edge.left, state.sweep) {
+                parabola_y(&edge.right, state.sweep, x)
+            } else {
+                parabola_y(&edge.left, state.sweep, x)
+            };
+            if let Some(edge) = clip_to_box(&edge.start, &Point::new(x, y.min(MAX_Y))) {
+                dc.draw_line(&edge.a, &edge.b, BLUE_COLOR);
+            }
+        }
+        for (i, arc) in state.arcs.iter().enumerate() {
+            if on_sweep(&arc.site, state.sweep) {
+                continue;
+            }
+            // only the part of the arc below `2 * MAX_Y` is drawn
+            let (site, sweep) = (arc.site, state.sweep);
+            let half_width = ((site.y - sweep) * (4. * MAX_Y - site.y - sweep)).sqrt();
+            let mut from = (site.x - half_width).max(0.);
+            let mut to = (site.x + half_width).min(MAX_X);
+            if i > 0 {
+                from = from.max(state.breakpoint_x(i - 1));
+            }
+            if i + 1 < state.arcs.len() {
+                to = to.min(state.breakpoint_x(i));
+            }
+            if from >= to {
+                continue;
+            }
+            let y0 = parabola_y(&arc.site, state.sweep, from);
+            let slope = (from - arc.site.x) / (arc.site.y - state.sweep);
+            dc.draw_quadratic(
+                &Point::new(from, y0),
+                &Point::new((from + to) / 2., y0 + slope * (to - from) / 2.),
+                &Point::new(to, parabola_y(&arc.site, state.sweep, to)),
+                GREEN_COLOR,
+            );
+        }
+        if !state.done {
+            dc.draw_line(
+                &Point::new(0., state.sweep),
+                &Point::new(MAX_X, state.sweep),
+                YELLOW_COLOR,
+            );
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Site((site, start)) => {
+                dc.draw_path(&[*site, *start], YELLOW_COLOR);
+            }
+            Action::Circle((center, r, site)) => {
+                dc.draw_circle(center, *r, RED_COLOR);
+                dc.draw_point(center, RED_COLOR);
+                dc.draw_point(site, RED_COLOR);
+            }
+            Action::Clip(edges) => {
+                for edge in edges {
+                    dc.draw_line(&edge.a, &edge.b, GREEN_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::algos::graham_common::{sort_by_angle, step};
+    use crate::generators::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn vertices_are_centers_of_empty_circles() {
+        for seed in 0..20 {
+            let mut points = Uniform.generate(40, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<VoronoiFortune>(points.clone());
+            let vertices = &states.last().unwrap().vertices;
+            for v in vertices {
+                let mut dist: Vec<f32> = points.iter().map(|p| (*p - *v).length()).collect();
+                dist.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+                assert!(dist[2] - dist[0] < 1e-3, "seed {}: {:?}", seed, v);
+            }
+
+            let n = points.len();
+            sort_by_angle(&mut points);
+            let mut hull = Vec::new();
+            while !points.is_empty() {
+                step(&mut points, &mut hull, |a, b, c| rotation(a, b, c) > 0.);
+            }
+            assert_eq!(2 * n - 2 - hull.len(), vertices.len(), "seed {}", seed);
+        }
+    }
+}
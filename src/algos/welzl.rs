@@ -0,0 +1,209 @@
+use crate::algos::Algo;
+use crate::canvas::*;
+use crate::common::*;
+
+pub type Circle = (Point, f32);
+
+// Points within this distance outside of the circle are still considered enclosed
+const TOLERANCE: f32 = 1e-4;
+
+fn contains(circle: &Option<Circle>, p: &Point) -> bool {
+    match circle {
+        Some((center, r)) => (*p - *center).length() <= r + TOLERANCE,
+        None => false,
+    }
+}
+
+fn diameter_circle(a: &Point, b: &Point) -> Circle {
+    (a.lerp(*b, 0.5), (*b - *a).length() / 2.)
+}
+
+// Smallest circle with all of the (at most three) points on its boundary
+fn circle_through(fixed: &[Point]) -> Circle {
+    match fixed {
+        [a] => (*a, 0.),
+        [a, b] => diameter_circle(a, b),
+        [a, b, c] => circumcircle(a, b, c).unwrap_or_else(|| {
+            // collinear points, the circle is spanned by the farthest pair
+            let pairs = [(a, b), (b, c), (a, c)];
+            let (p, q) = pairs
+                .iter()
+                .max_by(|x, y| {
+                    (*x.1 - *x.0)
+                        .length()
+                        .partial_cmp(&(*y.1 - *y.0).length())
+                        .unwrap()
+                })
+                .unwrap();
+            diameter_circle(p, q)
+        }),
+        _ => unreachable!(),
+    }
+}
+
+// Loop over `points[next..end]` looking for the smallest circle with `fixed` points on the boundary
+#[derive(Clone, Debug)]
+struct Level {
+    fixed: Vec<Point>,
+    next: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct State {
+    points: Vec<Point>,
+    circle: Option<Circle>,
+    levels: Vec<Level>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    NoAction,
+    Inside(Point),
+    Violation((Option<Circle>, Point, Vec<Point>)),
+    Restart((Circle, Vec<Point>)),
+}
+
+pub struct Welzl;
+
+impl Algo for Welzl {
+    type State = State;
+    type Action = Action;
+    type Input = Vec<Point>;
+
+    fn first_state(points: Vec<Point>) -> State {
+        let end = points.len();
+        State {
+            points,
+            circle: None,
+            levels: vec![Level {
+                fixed: Vec::new(),
+                next: 0,
+                end,
+            }],
+        }
+    }
+
+    fn next_state(mut state: State) -> (State, Action) {
+        let level = match state.levels.last_mut() {
+            Some(level) => level,
+            None => return (state, Action::NoAction),
+        };
+        if level.next == level.end {
+            // the circle is now the smallest one for the violating point of the outer level
+            state.levels.pop();
+            if let Some(outer) = state.levels.last_mut() {
+                outer.next += 1;
+            }
+            let fixed = state.levels.last().map_or(Vec::new(), |l| l.fixed.clone());
+            let circle = state.circle.unwrap();
+            return (state, Action::Restart((circle, fixed)));
+        }
+        let p = state.points[level.next];
+        if contains(&state.circle, &p) {
+            level.next += 1;
+            return (state, Action::Inside(p));
+        }
+
+        // `p` has to be on the boundary of the circle enclosing the points before it
+        let old = state.circle;
+        let mut fixed = level.fixed.clone();
+        fixed.push(p);
+        state.circle = Some(circle_through(&fixed));
+        if fixed.len() == 3 {
+            level.next += 1;
+        } else {
+            let end = level.next;
+            state.levels.push(Level {
+                fixed: fixed.clone(),
+                next: 0,
+                end,
+            });
+        }
+        (state, Action::Violation((old, p, fixed)))
+    }
+
+    fn is_final(state: &State) -> bool {
+        state.levels.is_empty()
+    }
+
+    fn draw_state(dc: &mut dyn Canvas, state: &State) {
+        if let Some((center, r)) = state.circle {
+            dc.fill_circle(&center, r, BLUE_COLOR);
+            dc.draw_circle(&center, r, BLUE_COLOR);
+        }
+        let end = state.levels.last().map_or(state.points.len(), |l| l.end);
+        for (i, point) in state.points.iter().enumerate() {
+            let color = if i < end { WHITE_COLOR } else { GRAY_COLOR };
+            dc.draw_point(point, color);
+        }
+        if let Some(level) = state.levels.last() {
+            for point in &level.fixed {
+                dc.draw_point(point, YELLOW_COLOR);
+            }
+        }
+    }
+
+    fn draw_action(dc: &mut dyn Canvas, action: &Action) {
+        match action {
+            Action::NoAction => {}
+            Action::Inside(p) => dc.draw_point(p, GREEN_COLOR),
+            Action::Violation((old, p, fixed)) => {
+                if let Some((center, r)) = old {
+                    dc.draw_circle(center, *r, RED_COLOR);
+                }
+                for point in fixed {
+                    dc.draw_point(point, YELLOW_COLOR);
+                }
+                dc.draw_point(p, RED_COLOR);
+            }
+            Action::Restart(((center, r), fixed)) => {
+                dc.draw_circle(center, *r, GREEN_COLOR);
+                for point in fixed {
+                    dc.draw_point(point, YELLOW_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::all_states;
+    use crate::generators::{insertion_order, Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn encloses_points_with_the_smallest_circle() {
+        for seed in 0..20 {
+            let points = Uniform.generate(40, &mut StdRng::seed_from_u64(seed));
+            let (states, _) = all_states::<Welzl>(insertion_order(points.clone(), seed));
+            let circle = states.last().unwrap().circle;
+            assert!(points.iter().all(|p| contains(&circle, p)), "seed {}", seed);
+
+            // no circle through two or three of the points encloses them all and is smaller
+            let r = circle.unwrap().1;
+            let n = points.len();
+            for i in 0..n {
+                for j in i + 1..n {
+                    let mut candidates = vec![diameter_circle(&points[i], &points[j])];
+                    for k in j + 1..n {
+                        candidates.extend(circumcircle(&points[i], &points[j], &points[k]));
+                    }
+                    for candidate in candidates {
+                        if candidate.1 < r - 1e-3 {
+                            let candidate = Some(candidate);
+                            assert!(
+                                points.iter().any(|p| !contains(&candidate, p)),
+                                "seed {}",
+                                seed
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
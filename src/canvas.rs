@@ -0,0 +1,111 @@
+use crate::common::*;
+use raqote::{SolidSource, Transform};
+
+pub const WHITE_COLOR: SolidSource = SolidSource {
+    r: 0xff,
+    g: 0xff,
+    b: 0xff,
+    a: 0xff,
+};
+pub const BLUE_COLOR: SolidSource = SolidSource {
+    r: 0,
+    g: 0,
+    b: 0xff,
+    a: 0xff,
+};
+pub const GREEN_COLOR: SolidSource = SolidSource {
+    r: 0,
+    g: 0xff,
+    b: 0,
+    a: 0xff,
+};
+pub const YELLOW_COLOR: SolidSource = SolidSource {
+    r: 0xff,
+    g: 0xff,
+    b: 0,
+    a: 0xff,
+};
+pub const RED_COLOR: SolidSource = SolidSource {
+    r: 0xff,
+    g: 0,
+    b: 0,
+    a: 0xff,
+};
+pub const GRAY_COLOR: SolidSource = SolidSource {
+    r: 0x50,
+    g: 0x50,
+    b: 0x50,
+    a: 0xff,
+};
+
+// Maps world coordinates in [0, MAX_X] x [0, MAX_Y] to a square image with y going up
+pub fn world_transform(size: (usize, usize)) -> Transform {
+    let transform = Transform::create_translation(1., -MAX_Y - 1.);
+    transform.post_scale(
+        (size.0 as f32) / (MAX_X + 2.0),
+        -(size.0 as f32) / (MAX_Y + 2.0),
+    )
+}
+
+pub trait Canvas {
+    fn draw_width(&self) -> f32;
+
+    fn clear(&mut self);
+    fn draw_point(&mut self, a: &Point, color: SolidSource);
+    fn draw_polyline(&mut self, points: &[Point], color: SolidSource);
+    fn fill_rect(&mut self, lb: &Point, rt: &Point, color: SolidSource);
+    fn draw_circle(&mut self, center: &Point, r: f32, color: SolidSource);
+    fn fill_circle(&mut self, center: &Point, r: f32, color: SolidSource);
+    fn fill_polygon(&mut self, points: &[Point], color: SolidSource);
+    fn draw_quadratic(&mut self, a: &Point, control: &Point, b: &Point, color: SolidSource);
+
+    fn draw_line(&mut self, a: &Point, b: &Point, color: SolidSource) {
+        self.draw_polyline(&[*a, *b], color);
+    }
+
+    // The part of the line inside of the view box
+    fn draw_infinite_line(&mut self, line: &Line, color: SolidSource) {
+        let d = (line.b - line.a).normalize() * 2. * (MAX_X + MAX_Y);
+        if let Some(part) = clip_to_box(&(line.a - d), &(line.a + d)) {
+            self.draw_line(&part.a, &part.b, color);
+        }
+    }
+
+    fn draw_path(&mut self, points: &[Point], color: SolidSource) {
+        if points.is_empty() {
+            return;
+        }
+        for point in points {
+            self.draw_point(point, color);
+        }
+        self.draw_polyline(points, color);
+    }
+
+    // Closed path from the last point back to the first one
+    fn draw_polygon(&mut self, points: &[Point], color: SolidSource) {
+        self.draw_path(points, color);
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            self.draw_line(last, first, color);
+        }
+    }
+
+    fn draw_rect(&mut self, rect: &Rect, color: SolidSource) {
+        let (lb, rt) = (rect.lb, rect.rt);
+        self.draw_polyline(
+            &[lb, Point::new(rt.x, lb.y), rt, Point::new(lb.x, rt.y), lb],
+            color,
+        );
+    }
+
+    fn fill_part(&mut self, left_x: f32, right_x: f32, color: SolidSource) {
+        self.fill_rect(&Point::new(left_x, 0.), &Point::new(right_x, MAX_Y), color);
+    }
+
+    fn draw_vertical_line(&mut self, x: f32, color: SolidSource) {
+        self.draw_line(&Point::new(x, 0.), &Point::new(x, MAX_Y), color);
+    }
+
+    fn draw_borders(&mut self, borders: &HorBorders) {
+        self.fill_part(borders.l, borders.r, GREEN_COLOR);
+    }
+}
@@ -13,6 +13,97 @@ pub fn rotation(a: &Point, b: &Point, c: &Point) -> f32 {
     a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y)
 }
 
+// Same as `rotation`, but computed in f64 around `a`. The differences of f32 coordinates are exact,
+// yet may need up to ~48 bits, so their products aren't always exact: the sign can only be wrong
+// when the points are almost collinear
+pub fn exact_rotation(a: &Point, b: &Point, c: &Point) -> f64 {
+    let d = |p: &Point, q: &Point| {
+        (
+            f64::from(p.x) - f64::from(q.x),
+            f64::from(p.y) - f64::from(q.y),
+        )
+    };
+    let (u, v) = (d(b, a), d(c, a));
+    u.0 * v.1 - u.1 * v.0
+}
+
+// Positive if `d` is inside the circumcircle of the counterclockwise triangle `a, b, c`,
+// negative if outside. Computed in f64 around `d`, where differences of f32 coordinates are exact
+pub fn in_circle(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+    let lift = |p: &Point| {
+        let (x, y) = (
+            f64::from(p.x) - f64::from(d.x),
+            f64::from(p.y) - f64::from(d.y),
+        );
+        (x, y, x * x + y * y)
+    };
+    let (ax, ay, al) = lift(a);
+    let (bx, by, bl) = lift(b);
+    let (cx, cy, cl) = lift(c);
+    ax * (by * cl - bl * cy) - ay * (bx * cl - bl * cx) + al * (bx * cy - by * cx)
+}
+
+// Center and radius of the circle through three points, `None` if they are collinear
+pub fn circumcircle(a: &Point, b: &Point, c: &Point) -> Option<(Point, f32)> {
+    let (b, c) = (*b - *a, *c - *a);
+    let d = 2. * (b.x * c.y - b.y * c.x);
+    if eps_equal(d, 0.) {
+        return None;
+    }
+    let (bl, cl) = (b.square_length(), c.square_length());
+    let center = euclid::vec2(c.y * bl - b.y * cl, b.x * cl - c.x * bl) / d;
+    Some((*a + center, center.length()))
+}
+
+// Part of the segment `a -> b` inside [0, MAX_X] x [0, MAX_Y]
+pub fn clip_to_box(a: &Point, b: &Point) -> Option<Pair> {
+    clip_to_rect(a, b, &Rect::full())
+}
+
+// Part of the segment `a -> b` inside of the rectangle (Liang-Barsky)
+pub fn clip_to_rect(a: &Point, b: &Point, rect: &Rect) -> Option<Pair> {
+    let d = *b - *a;
+    let (mut t0, mut t1) = (0f32, 1f32);
+    for (p, q) in [
+        (-d.x, a.x - rect.lb.x),
+        (d.x, rect.rt.x - a.x),
+        (-d.y, a.y - rect.lb.y),
+        (d.y, rect.rt.y - a.y),
+    ]
+    .iter()
+    {
+        if eps_equal(*p, 0.) {
+            if *q < 0. {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if *p < 0. {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+    if t0 > t1 {
+        None
+    } else {
+        Some(Pair::new(*a + d * t0, *a + d * t1))
+    }
+}
+
+// Positive for counterclockwise polygons
+pub fn signed_area(polygon: &[Point]) -> f32 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.
+}
+
 pub fn cmp_by_x(a: &Point, b: &Point) -> std::cmp::Ordering {
     a.x.partial_cmp(&b.x).unwrap()
 }
@@ -45,7 +136,7 @@ impl Pair {
     pub fn inf() -> Self {
         Self {
             a: Point::new(0., 0.),
-            b: Point::new(std::f32::INFINITY, std::f32::INFINITY),
+            b: Point::new(f32::INFINITY, f32::INFINITY),
         }
     }
     pub fn square_len(&self) -> f32 {
@@ -53,6 +144,42 @@ impl Pair {
     }
 }
 
+// Axis-aligned rectangle from the left bottom to the right top corner
+#[derive(Copy, Clone, Debug)]
+pub struct Rect {
+    pub lb: Point,
+    pub rt: Point,
+}
+
+impl Rect {
+    pub fn new(lb: Point, rt: Point) -> Self {
+        Self { lb, rt }
+    }
+    pub fn full() -> Self {
+        Self::new(Point::new(0., 0.), Point::new(MAX_X, MAX_Y))
+    }
+    pub fn center(&self) -> Point {
+        self.lb.lerp(self.rt, 0.5)
+    }
+    pub fn contains(&self, p: &Point) -> bool {
+        self.lb.x <= p.x && p.x <= self.rt.x && self.lb.y <= p.y && p.y <= self.rt.y
+    }
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.contains(&other.lb) && self.contains(&other.rt)
+    }
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.lb.x <= other.rt.x
+            && other.lb.x <= self.rt.x
+            && self.lb.y <= other.rt.y
+            && other.lb.y <= self.rt.y
+    }
+    pub fn distance(&self, p: &Point) -> f32 {
+        let dx = (self.lb.x - p.x).max(p.x - self.rt.x).max(0.);
+        let dy = (self.lb.y - p.y).max(p.y - self.rt.y).max(0.);
+        dx.hypot(dy)
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct IndexBorders {
     pub l: usize,
@@ -91,3 +218,117 @@ impl HorBorders {
         }
     }
 }
+
+// Segment from its left end `a` to the right end `b`, the lower end is left on vertical segments
+#[derive(Copy, Clone, Debug)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Segment {
+    pub fn new(mut a: Point, mut b: Point) -> Segment {
+        if cmp_by_xy(&a, &b) == std::cmp::Ordering::Greater {
+            std::mem::swap(&mut a, &mut b);
+        }
+        Segment { a, b }
+    }
+    pub fn get_y(&self, x: f32) -> f32 {
+        if eps_equal(self.a.x, self.b.x) {
+            self.a.y
+        } else {
+            self.a.y + (self.b.y - self.a.y) * (x - self.a.x) / (self.b.x - self.a.x)
+        }
+    }
+    pub fn slope(&self) -> f32 {
+        if eps_equal(self.a.x, self.b.x) {
+            f32::INFINITY
+        } else {
+            (self.b.y - self.a.y) / (self.b.x - self.a.x)
+        }
+    }
+}
+
+// Order from bottom to top at the left end of the later segment
+impl Ord for Segment {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let x = self.a.x.max(other.a.x);
+        self.get_y(x)
+            .partial_cmp(&other.get_y(x))
+            .unwrap()
+            // segments meeting at x are ordered as they go to the right of it
+            .then_with(|| self.slope().partial_cmp(&other.slope()).unwrap())
+            .then_with(|| self.a.x.partial_cmp(&other.a.x).unwrap())
+            .then_with(|| self.a.y.partial_cmp(&other.a.y).unwrap())
+            .then_with(|| self.b.x.partial_cmp(&other.b.x).unwrap())
+            .then_with(|| self.b.y.partial_cmp(&other.b.y).unwrap())
+    }
+}
+impl PartialOrd for Segment {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for Segment {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+impl Eq for Segment {}
+
+fn intersect_1d(mut a1: f32, mut a2: f32, mut b1: f32, mut b2: f32) -> bool {
+    if a1 > a2 {
+        std::mem::swap(&mut a1, &mut a2)
+    }
+    if b1 > b2 {
+        std::mem::swap(&mut b1, &mut b2)
+    }
+    a1.max(b1) <= a2.min(b2)
+}
+
+pub fn intersect_seg(first: &Segment, second: &Segment) -> bool {
+    intersect_1d(first.a.x, first.b.x, second.a.x, second.b.x)
+        && intersect_1d(first.a.y, first.b.y, second.a.y, second.b.y)
+        && rotation(&first.a, &first.b, &second.a) * rotation(&first.a, &first.b, &second.b) <= 0.
+        && rotation(&second.a, &second.b, &first.a) * rotation(&second.a, &second.b, &first.b) <= 0.
+}
+
+// Directed line through `a` and `b`, bounding the half-plane to the left of it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Line {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Line {
+    pub fn new(a: Point, b: Point) -> Line {
+        Line { a, b }
+    }
+    // Through `p` with the center of the area on the left, horizontal at the center itself
+    pub fn facing_center(p: Point) -> Line {
+        let d = (p - Rect::full().center())
+            .try_normalize()
+            .unwrap_or_else(|| euclid::vec2(0., -1.));
+        Line::new(p, p + euclid::vec2(-d.y, d.x))
+    }
+    pub fn angle(&self) -> f32 {
+        let d = self.b - self.a;
+        d.y.atan2(d.x)
+    }
+    // Signed distance from the line, positive inside of the half-plane
+    pub fn distance(&self, p: &Point) -> f32 {
+        rotation(&self.a, &self.b, p) / (self.b - self.a).length()
+    }
+    pub fn is_parallel(&self, other: &Line) -> bool {
+        let (d, e) = (self.b - self.a, other.b - other.a);
+        eps_equal(d.cross(e) / d.length() / e.length(), 0.)
+    }
+    pub fn intersection(&self, other: &Line) -> Option<Point> {
+        let (d, e) = (self.b - self.a, other.b - other.a);
+        let den = d.cross(e);
+        if eps_equal(den, 0.) {
+            return None;
+        }
+        Some(self.a + d * ((other.a - self.a).cross(e) / den))
+    }
+}
@@ -1,67 +1,54 @@
+use crate::canvas::*;
 use crate::common::*;
 use raqote::{
-    AntialiasMode, BlendMode, DrawOptions, DrawTarget, LineCap, LineJoin, Path, PathBuilder,
-    SolidSource, Source, StrokeStyle, Transform,
+    AntialiasMode, BlendMode, DrawOptions, DrawTarget, LineCap, LineJoin, PathBuilder, SolidSource,
+    Source, StrokeStyle, Transform,
 };
 
-pub const WHITE_COLOR: SolidSource = SolidSource {
-    r: 0xff,
-    g: 0xff,
-    b: 0xff,
-    a: 0xff,
-};
-pub const BLUE_COLOR: SolidSource = SolidSource {
-    r: 0,
-    g: 0,
-    b: 0xff,
-    a: 0xff,
-};
-pub const GREEN_COLOR: SolidSource = SolidSource {
-    r: 0,
-    g: 0xff,
-    b: 0,
-    a: 0xff,
-};
-pub const YELLOW_COLOR: SolidSource = SolidSource {
-    r: 0xff,
-    g: 0xff,
-    b: 0,
-    a: 0xff,
-};
-pub const RED_COLOR: SolidSource = SolidSource {
-    r: 0xff,
-    g: 0,
-    b: 0,
-    a: 0xff,
-};
+// raqote's rasterizer overflows on edges longer than 2^15 pixels, so farther lines are clipped
+// to the view and bigger circles are drawn as a polyline through the visible part
+const MAX_PIXEL_RADIUS: f32 = 8192.;
+const ARC_SEGMENTS: usize = 64;
 
 pub struct DrawContext {
     dt: DrawTarget,
     draw_width: f32,
+    inverse: Transform,
 }
 
 impl DrawContext {
     pub fn new(size: (usize, usize), draw_width: f32) -> Self {
         let mut dt = DrawTarget::new(size.0 as i32, size.1 as i32);
-        let transform = Transform::create_translation(1., -MAX_Y - 1.);
-        let transform = transform.post_scale(
-            (size.0 as f32) / (MAX_X + 2.0),
-            -(size.0 as f32) / (MAX_Y + 2.0),
-        );
+        let transform = world_transform(size);
         dt.set_transform(&transform);
-        Self { dt, draw_width }
+        Self {
+            dt,
+            draw_width,
+            inverse: transform.inverse().unwrap(),
+        }
     }
 
-    pub fn clear(self: &mut Self) {
-        self.dt
-            .clear(SolidSource::from_unpremultiplied_argb(0, 0, 0, 0xff));
+    pub fn to_world(&self, x: f32, y: f32) -> Point {
+        let p = self.inverse.transform_point(euclid::point2(x, y));
+        Point::new(p.x, p.y)
     }
 
-    pub fn get_data(self: &Self) -> &[u32] {
+    pub fn get_data(&self) -> &[u32] {
         self.dt.get_data()
     }
+}
+
+impl Canvas for DrawContext {
+    fn draw_width(&self) -> f32 {
+        self.draw_width
+    }
+
+    fn clear(&mut self) {
+        self.dt
+            .clear(SolidSource::from_unpremultiplied_argb(0, 0, 0, 0xff));
+    }
 
-    pub fn draw_point(self: &mut Self, a: &Point, color: SolidSource) {
+    fn draw_point(&mut self, a: &Point, color: SolidSource) {
         let mut pb = PathBuilder::new();
         let r = self.draw_width;
         pb.move_to(a.x + r, a.y);
@@ -72,9 +59,24 @@ impl DrawContext {
             .fill(&path, &Source::Solid(color), &DrawOptions::new());
     }
 
-    fn draw_rpath(self: &mut Self, path: &Path, color: SolidSource) {
+    fn draw_polyline(&mut self, points: &[Point], color: SolidSource) {
+        let max = MAX_PIXEL_RADIUS * self.inverse.m11;
+        let view = Rect::new(Point::new(-1., -1.), Point::new(MAX_X + 1., MAX_Y + 1.));
+        if points.iter().any(|p| p.x.abs().max(p.y.abs()) > max) {
+            for pair in points.windows(2) {
+                if let Some(part) = clip_to_rect(&pair[0], &pair[1], &view) {
+                    self.draw_polyline(&[part.a, part.b], color);
+                }
+            }
+            return;
+        }
+        let mut pb = PathBuilder::new();
+        pb.move_to(points[0].x, points[0].y);
+        for point in points {
+            pb.line_to(point.x, point.y);
+        }
         self.dt.stroke(
-            path,
+            &pb.finish(),
             &Source::Solid(color),
             &StrokeStyle {
                 width: self.draw_width / 1.5,
@@ -86,28 +88,93 @@ impl DrawContext {
         );
     }
 
-    pub fn draw_line(self: &mut Self, a: &Point, b: &Point, color: SolidSource) {
+    fn draw_circle(&mut self, center: &Point, r: f32, color: SolidSource) {
+        if r / self.inverse.m11 > MAX_PIXEL_RADIUS {
+            // only the arc that can reach the view, points within the view's half-diagonal
+            // of its closest point are at most twice that far from it
+            let to_view = Point::new(MAX_X / 2., MAX_Y / 2.) - *center;
+            let half_diagonal = (MAX_X + 2.).hypot(MAX_Y + 2.) / 2.;
+            if (to_view.length() - r).abs() > half_diagonal {
+                return;
+            }
+            let angle = to_view.y.atan2(to_view.x);
+            let spread = 2. * (half_diagonal / r).min(1.).asin();
+            let arc: Vec<Point> = (0..=ARC_SEGMENTS)
+                .map(|i| {
+                    let a = angle - spread + 2. * spread * i as f32 / ARC_SEGMENTS as f32;
+                    *center + euclid::vec2(a.cos(), a.sin()) * r
+                })
+                .collect();
+            self.draw_polyline(&arc, color);
+            return;
+        }
         let mut pb = PathBuilder::new();
-        pb.move_to(a.x, a.y);
-        pb.line_to(b.x, b.y);
-        self.draw_rpath(&pb.finish(), color);
+        pb.move_to(center.x + r, center.y);
+        pb.arc(center.x, center.y, r, 0.0, 2.0 * std::f32::consts::PI);
+        pb.close();
+        self.dt.stroke(
+            &pb.finish(),
+            &Source::Solid(color),
+            &StrokeStyle {
+                width: self.draw_width / 1.5,
+                ..StrokeStyle::default()
+            },
+            &DrawOptions::new(),
+        );
     }
 
-    pub fn draw_path(self: &mut Self, points: &[Point], color: SolidSource) {
+    fn fill_circle(&mut self, center: &Point, r: f32, color: SolidSource) {
+        let mut pb = PathBuilder::new();
+        pb.move_to(center.x + r, center.y);
+        pb.arc(center.x, center.y, r, 0.0, 2.0 * std::f32::consts::PI);
+        pb.close();
+        self.dt.fill(
+            &pb.finish(),
+            &Source::Solid(color),
+            &DrawOptions {
+                alpha: 0.3,
+                ..DrawOptions::new()
+            },
+        );
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], color: SolidSource) {
         if points.is_empty() {
             return;
         }
-        let first = points[0];
         let mut pb = PathBuilder::new();
-        pb.move_to(first.x, first.y);
-        for point in points {
-            self.draw_point(point, color);
+        pb.move_to(points[0].x, points[0].y);
+        for point in &points[1..] {
             pb.line_to(point.x, point.y);
         }
-        self.draw_rpath(&pb.finish(), color);
+        pb.close();
+        self.dt.fill(
+            &pb.finish(),
+            &Source::Solid(color),
+            &DrawOptions {
+                alpha: 0.3,
+                ..DrawOptions::new()
+            },
+        );
+    }
+
+    fn draw_quadratic(&mut self, a: &Point, control: &Point, b: &Point, color: SolidSource) {
+        let mut pb = PathBuilder::new();
+        pb.move_to(a.x, a.y);
+        pb.quad_to(control.x, control.y, b.x, b.y);
+        self.dt.stroke(
+            &pb.finish(),
+            &Source::Solid(color),
+            &StrokeStyle {
+                width: self.draw_width / 1.5,
+                cap: LineCap::Round,
+                ..StrokeStyle::default()
+            },
+            &DrawOptions::new(),
+        );
     }
 
-    pub fn fill_rect(self: &mut Self, lb: &Point, rt: &Point, color: SolidSource) {
+    fn fill_rect(&mut self, lb: &Point, rt: &Point, color: SolidSource) {
         self.dt.fill_rect(
             lb.x - self.draw_width / 2.0,
             lb.y - self.draw_width / 2.0,
@@ -121,15 +188,4 @@ impl DrawContext {
             },
         )
     }
-    pub fn fill_part(self: &mut Self, left_x: f32, right_x: f32, color: SolidSource) {
-        self.fill_rect(&Point::new(left_x, 0.), &Point::new(right_x, MAX_Y), color);
-    }
-
-    pub fn draw_vertical_line(self: &mut Self, x: f32, color: SolidSource) {
-        self.draw_line(&Point::new(x, 0.), &Point::new(x, MAX_Y), color);
-    }
-
-    pub fn draw_borders(self: &mut Self, borders: &HorBorders) {
-        self.fill_part(borders.l, borders.r, GREEN_COLOR);
-    }
 }
@@ -0,0 +1,141 @@
+use crate::algos::{all_states, draw_step, Algo};
+use crate::canvas::Canvas;
+use crate::draw_context::DrawContext;
+use crate::svg::SvgCanvas;
+use gif::{Encoder, Frame, Repeat};
+use raqote::DrawTarget;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::Path;
+
+pub struct GifOptions {
+    pub size: usize,
+    pub draw_width: f32,
+    // in milliseconds, GIF stores it in hundredths of a second
+    pub delay: u32,
+    pub hold_frames: usize,
+}
+
+// Alpha is ignored like minifb does, the cleared background is transparent black
+fn opaque(pixel: u32) -> u32 {
+    pixel | 0xff00_0000
+}
+
+fn to_rgba(data: &[u32]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len() * 4);
+    for pixel in data {
+        let [a, r, g, b] = opaque(*pixel).to_be_bytes();
+        res.extend_from_slice(&[r, g, b, a]);
+    }
+    res
+}
+
+// Calls `f` for every step exactly as `ui::show` alternates states and actions
+fn render_steps<TAlgo, C, F>(input: TAlgo::Input, mut dc: C, mut f: F) -> Result<(), String>
+where
+    TAlgo: Algo,
+    C: Canvas,
+    F: FnMut(usize, bool, &C) -> Result<(), String>,
+{
+    let (states, actions) = all_states::<TAlgo>(input);
+    let last_index = actions.len() * 2;
+    for index in 0..=last_index {
+        dc.clear();
+        draw_step::<TAlgo>(&mut dc, &states, &actions, index);
+        f(index, index == last_index, &dc)?;
+    }
+    Ok(())
+}
+
+pub fn export_gif<TAlgo>(
+    input: TAlgo::Input,
+    path: &str,
+    options: &GifOptions,
+) -> Result<(), String>
+where
+    TAlgo: Algo,
+{
+    let size = u16::try_from(options.size)
+        .map_err(|_| format!("GIF size {} is larger than {}", options.size, u16::MAX))?;
+    let delay = u16::try_from(options.delay / 10)
+        .map_err(|_| format!("GIF delay {} ms is too long", options.delay))?;
+    let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+    let mut encoder =
+        Encoder::new(file, size, size, &[]).map_err(|e| format!("failed to encode GIF: {}", e))?;
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| format!("failed to encode GIF: {}", e))?;
+
+    let dc = DrawContext::new((options.size, options.size), options.draw_width);
+    render_steps::<TAlgo, _, _>(input, dc, |_, is_last, dc| {
+        let mut frame = Frame::from_rgba_speed(size, size, &mut to_rgba(dc.get_data()), 10);
+        frame.delay = delay;
+        let repeats = if is_last { 1 + options.hold_frames } else { 1 };
+        for _ in 0..repeats {
+            encoder
+                .write_frame(&frame)
+                .map_err(|e| format!("failed to encode GIF: {}", e))?;
+        }
+        Ok(())
+    })
+}
+
+pub fn export_frames<TAlgo>(
+    input: TAlgo::Input,
+    dir: &str,
+    size: usize,
+    draw_width: f32,
+) -> Result<(), String>
+where
+    TAlgo: Algo,
+{
+    std::fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {}", dir, e))?;
+    let dc = DrawContext::new((size, size), draw_width);
+    render_steps::<TAlgo, _, _>(input, dc, |index, _, dc| {
+        let path = Path::new(dir).join(format!("{:04}.png", index));
+        let data = dc.get_data().iter().map(|p| opaque(*p)).collect();
+        DrawTarget::from_vec(size as i32, size as i32, data)
+            .write_png(&path)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    })
+}
+
+fn write_svg(path: &Path, dc: &SvgCanvas) -> Result<(), String> {
+    std::fs::write(path, dc.to_svg())
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+// Writes a single step to `path` if `step` is given, otherwise writes all steps into `path` directory
+pub fn export_svg<TAlgo>(
+    input: TAlgo::Input,
+    path: &str,
+    step: Option<usize>,
+    size: usize,
+    draw_width: f32,
+) -> Result<(), String>
+where
+    TAlgo: Algo,
+{
+    let dc = SvgCanvas::new((size, size), draw_width);
+    match step {
+        Some(step) => {
+            let mut found = false;
+            render_steps::<TAlgo, _, _>(input, dc, |index, is_last, dc| {
+                if index == step {
+                    found = true;
+                    write_svg(Path::new(path), dc)?;
+                } else if is_last && !found {
+                    return Err(format!("step {} is out of range 0..={}", step, index));
+                }
+                Ok(())
+            })
+        }
+        None => {
+            std::fs::create_dir_all(path)
+                .map_err(|e| format!("failed to create {}: {}", path, e))?;
+            render_steps::<TAlgo, _, _>(input, dc, |index, _, dc| {
+                write_svg(&Path::new(path).join(format!("{:04}.svg", index)), dc)
+            })
+        }
+    }
+}
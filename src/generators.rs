@@ -0,0 +1,261 @@
+use crate::common::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+
+pub trait Distribution {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point>;
+}
+
+fn center() -> Point {
+    Point::new(MAX_X / 2., MAX_Y / 2.)
+}
+
+fn is_inside(p: &Point) -> bool {
+    p.x >= 0. && p.x < MAX_X && p.y >= 0. && p.y < MAX_Y
+}
+
+// Tries to get a point inside of the area before falling back to a uniform one,
+// otherwise a large sigma would reject points almost forever
+const MAX_TRIES: usize = 100;
+
+fn draw_inside(rng: &mut dyn RngCore, mut draw: impl FnMut(&mut dyn RngCore) -> Point) -> Point {
+    for _ in 0..MAX_TRIES {
+        let p = draw(rng);
+        if is_inside(&p) {
+            return p;
+        }
+    }
+    Point::new(rng.gen_range(0., MAX_X), rng.gen_range(0., MAX_Y))
+}
+
+// Box-Muller transform, rand 0.7 doesn't have a normal distribution without rand_distr
+fn gaussian(rng: &mut dyn RngCore, sigma: f32) -> f32 {
+    let u: f32 = rng.gen_range(f32::EPSILON, 1.);
+    let v: f32 = rng.gen_range(0., 2. * std::f32::consts::PI);
+    sigma * (-2. * u.ln()).sqrt() * v.cos()
+}
+
+pub struct Uniform;
+
+impl Distribution for Uniform {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let mut res = Vec::with_capacity(n);
+        for _ in 0..n {
+            res.push(Point::new(
+                rng.gen_range(0., MAX_X),
+                rng.gen_range(0., MAX_Y),
+            ));
+        }
+        res
+    }
+}
+
+pub struct Circle;
+
+impl Distribution for Circle {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let r = MAX_X.min(MAX_Y) * 0.45;
+        (0..n)
+            .map(|_| {
+                let angle: f32 = rng.gen_range(0., 2. * std::f32::consts::PI);
+                center() + euclid::vec2(r * angle.cos(), r * angle.sin())
+            })
+            .collect()
+    }
+}
+
+pub struct Gaussian {
+    pub clusters: usize,
+    pub sigma: f32,
+}
+
+impl Distribution for Gaussian {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let margin = self.sigma.min(MAX_X / 4.);
+        let centers: Vec<Point> = (0..self.clusters)
+            .map(|_| {
+                Point::new(
+                    rng.gen_range(margin, MAX_X - margin),
+                    rng.gen_range(margin, MAX_Y - margin),
+                )
+            })
+            .collect();
+        (0..n)
+            .map(|i| {
+                let c = centers[i % centers.len()];
+                draw_inside(rng, |rng| {
+                    Point::new(
+                        c.x + gaussian(rng, self.sigma),
+                        c.y + gaussian(rng, self.sigma),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+pub struct Grid;
+
+impl Distribution for Grid {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let side = (n as f32).sqrt().ceil() as usize;
+        let step_x = MAX_X / side as f32;
+        let step_y = MAX_Y / side as f32;
+        let mut cells: Vec<usize> = (0..side * side).collect();
+        // Partial Fisher-Yates shuffle to pick n distinct cells
+        for i in 0..n {
+            let j = rng.gen_range(i, cells.len());
+            cells.swap(i, j);
+        }
+        cells[..n]
+            .iter()
+            .map(|c| {
+                Point::new(
+                    (c % side) as f32 * step_x + step_x / 2.,
+                    (c / side) as f32 * step_y + step_y / 2.,
+                )
+            })
+            .collect()
+    }
+}
+
+pub struct Bands {
+    pub bands: usize,
+    pub sigma: f32,
+}
+
+impl Distribution for Bands {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let slope = rng.gen_range(-0.5, 0.5);
+        let step = MAX_Y / (self.bands + 1) as f32;
+        (0..n)
+            .map(|i| {
+                let band = (i % self.bands + 1) as f32;
+                draw_inside(rng, |rng| {
+                    let x = rng.gen_range(0., MAX_X);
+                    Point::new(
+                        x,
+                        band * step + slope * (x - MAX_X / 2.) + gaussian(rng, self.sigma),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+pub struct Parabola;
+
+impl Distribution for Parabola {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let half = MAX_X / 2.;
+        (0..n)
+            .map(|_| {
+                let x = rng.gen_range(0., MAX_X);
+                Point::new(x, (x - half) * (x - half) * (MAX_Y - 1.) / (half * half))
+            })
+            .collect()
+    }
+}
+
+// Star-shaped around the center, so vertices sorted by angle form a counterclockwise simple polygon
+pub struct SimplePolygon;
+
+// The center is inside of the polygon only if the angles don't leave a half-turn empty
+fn is_star(angles: &[f32]) -> bool {
+    let full = 2. * std::f32::consts::PI;
+    let last = angles[angles.len() - 1];
+    let gaps = angles.windows(2).map(|w| w[1] - w[0]);
+    gaps.chain(std::iter::once(angles[0] + full - last))
+        .all(|gap| gap > 0. && gap < std::f32::consts::PI)
+}
+
+impl Distribution for SimplePolygon {
+    fn generate(&self, n: usize, rng: &mut dyn RngCore) -> Vec<Point> {
+        let r = MAX_X.min(MAX_Y) * 0.45;
+        let angles = loop {
+            let mut angles: Vec<f32> = (0..n)
+                .map(|_| rng.gen_range(0., 2. * std::f32::consts::PI))
+                .collect();
+            angles.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+            // fewer than 3 vertices are never a polygon
+            if n < 3 || is_star(&angles) {
+                break angles;
+            }
+        };
+        angles
+            .into_iter()
+            .map(|angle| {
+                let radius = r * rng.gen_range(0.2, 1.);
+                center() + euclid::vec2(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+}
+
+// Query point of an algorithm, from a different stream than the one generating the points
+pub fn random_point(seed: u64) -> Point {
+    Uniform.generate(1, &mut StdRng::seed_from_u64(!seed))[0]
+}
+
+// Query rectangle of an algorithm, from the same stream as `random_point`
+pub fn random_rect(seed: u64) -> Rect {
+    let corners = Uniform.generate(2, &mut StdRng::seed_from_u64(!seed));
+    Rect::new(corners[0].min(corners[1]), corners[0].max(corners[1]))
+}
+
+// Incremental algorithms insert items in the given order, so it is shuffled beforehand
+pub fn insertion_order<T>(mut items: Vec<T>, seed: u64) -> Vec<T> {
+    items.shuffle(&mut StdRng::seed_from_u64(seed));
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributions_are_reproducible_and_inside() {
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Uniform),
+            Box::new(Circle),
+            Box::new(Gaussian {
+                clusters: 3,
+                sigma: 1.,
+            }),
+            Box::new(Grid),
+            Box::new(Bands {
+                bands: 3,
+                sigma: 0.05,
+            }),
+            Box::new(Parabola),
+            Box::new(SimplePolygon),
+        ];
+        for distribution in distributions {
+            let first = distribution.generate(30, &mut StdRng::seed_from_u64(42));
+            let second = distribution.generate(30, &mut StdRng::seed_from_u64(42));
+            assert_eq!(30, first.len());
+            assert_eq!(first, second);
+            assert!(first.iter().all(is_inside));
+        }
+    }
+
+    #[test]
+    fn large_sigma_stays_inside() {
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Gaussian {
+                clusters: 3,
+                sigma: 1e5,
+            }),
+            Box::new(Bands {
+                bands: 3,
+                sigma: 1e5,
+            }),
+        ];
+        for distribution in distributions {
+            let points = distribution.generate(30, &mut StdRng::seed_from_u64(42));
+            assert_eq!(30, points.len());
+            assert!(points.iter().all(is_inside));
+        }
+    }
+}
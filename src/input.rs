@@ -0,0 +1,463 @@
+use crate::common::*;
+use serde_json::Value;
+use std::io::Read;
+
+fn parse_coord(s: &str, line: usize) -> Result<f32, String> {
+    let v = s
+        .trim()
+        .parse::<f32>()
+        .map_err(|e| format!("line {}: failed to parse '{}': {}", line, s.trim(), e))?;
+    if v.is_finite() {
+        Ok(v)
+    } else {
+        Err(format!(
+            "line {}: coordinate '{}' is not finite",
+            line,
+            s.trim()
+        ))
+    }
+}
+
+// Rows of `count` numbers separated by whitespace or commas, with their line numbers
+fn parse_rows(content: &str, count: usize) -> Result<Vec<(usize, Vec<f32>)>, String> {
+    let mut res = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = if line.contains(',') {
+            line.split(',').collect()
+        } else {
+            line.split_whitespace().collect()
+        };
+        if fields.len() != count {
+            return Err(format!(
+                "line {}: expected {} coordinates, found {}",
+                line_no,
+                count,
+                fields.len()
+            ));
+        }
+        if res.is_empty()
+            && line.contains(',')
+            && fields.iter().all(|f| f.trim().parse::<f32>().is_err())
+        {
+            // CSV header like "x,y"
+            continue;
+        }
+        let coords = fields
+            .iter()
+            .map(|f| parse_coord(f, line_no))
+            .collect::<Result<Vec<f32>, String>>()?;
+        res.push((line_no, coords));
+    }
+    Ok(res)
+}
+
+fn parse_text(content: &str) -> Result<Vec<(usize, Point)>, String> {
+    Ok(parse_rows(content, 2)?
+        .into_iter()
+        .map(|(line, c)| (line, Point::new(c[0], c[1])))
+        .collect())
+}
+
+fn json_coord(v: Option<&Value>, line: usize) -> Result<f32, String> {
+    v.and_then(Value::as_f64)
+        .map(|x| x as f32)
+        .ok_or_else(|| format!("line {}: expected a number", line))
+}
+
+// Array items given either as `[x, y, ..]` or as objects with the given `keys`
+fn parse_json_rows(
+    content: &str,
+    keys: &[&str],
+    what: &str,
+) -> Result<Vec<(usize, Vec<f32>)>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("expected a JSON array of {}", what))?;
+    // serde_json doesn't keep positions, so find the line of each item by its index
+    let item_lines = json_item_lines(content);
+    let mut res = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let line = item_lines.get(i).copied().unwrap_or(1);
+        let coords = match item {
+            Value::Array(a) if a.len() == keys.len() => a
+                .iter()
+                .map(|v| json_coord(Some(v), line))
+                .collect::<Result<Vec<f32>, String>>()?,
+            Value::Object(o) => keys
+                .iter()
+                .map(|k| json_coord(o.get(*k), line))
+                .collect::<Result<Vec<f32>, String>>()?,
+            _ => {
+                let fields: Vec<String> = keys.iter().map(|k| format!("\"{}\": ..", k)).collect();
+                return Err(format!(
+                    "line {}: expected [{}] or {{{}}}",
+                    line,
+                    keys.join(", "),
+                    fields.join(", ")
+                ));
+            }
+        };
+        res.push((line, coords));
+    }
+    Ok(res)
+}
+
+fn parse_json(content: &str) -> Result<Vec<(usize, Point)>, String> {
+    Ok(parse_json_rows(content, &["x", "y"], "points")?
+        .into_iter()
+        .map(|(line, c)| (line, Point::new(c[0], c[1])))
+        .collect())
+}
+
+fn json_item_lines(content: &str) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut line = 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut expect_item = false;
+    for c in content.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if !c.is_whitespace() {
+            if expect_item && depth == 1 && c != ']' {
+                res.push(line);
+            }
+            expect_item = false;
+            match c {
+                '[' | '{' => {
+                    depth += 1;
+                    expect_item = depth == 1;
+                }
+                ']' | '}' => depth -= 1,
+                ',' => expect_item = depth == 1,
+                '"' => in_string = true,
+                _ => {}
+            }
+        }
+        if c == '\n' {
+            line += 1;
+        }
+    }
+    res
+}
+
+fn check_bounds(p: &Point) -> Result<(), String> {
+    if p.x < 0. || p.x > MAX_X || p.y < 0. || p.y > MAX_Y {
+        Err(format!(
+            "point ({}, {}) is outside of [0, {}] x [0, {}]",
+            p.x, p.y, MAX_X, MAX_Y
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate(points: Vec<(usize, Point)>) -> Result<Vec<Point>, String> {
+    let mut sorted: Vec<(usize, Point)> = Vec::with_capacity(points.len());
+    for (line, p) in &points {
+        check_bounds(p).map_err(|e| format!("line {}: {}", line, e))?;
+        match sorted.binary_search_by(|(_, a)| cmp_by_xy(a, p)) {
+            Ok(idx) => {
+                return Err(format!(
+                    "line {}: point ({}, {}) duplicates line {}",
+                    line, p.x, p.y, sorted[idx].0
+                ))
+            }
+            Err(idx) => sorted.insert(idx, (*line, *p)),
+        }
+    }
+    if points.len() <= 3 {
+        return Err(String::from(
+            "the number of points should be greater than 3",
+        ));
+    }
+    Ok(points.into_iter().map(|(_, p)| p).collect())
+}
+
+pub fn parse_points(content: &str) -> Result<Vec<Point>, String> {
+    let points = if content.trim_start().starts_with('[') {
+        parse_json(content)?
+    } else {
+        parse_text(content)?
+    };
+    validate(points)
+}
+
+// Single point given on the command line as `x,y`
+pub fn parse_point(s: &str) -> Result<Point, String> {
+    match parse_text(s)?.as_slice() {
+        [(_, p)] => check_bounds(p).map(|_| *p),
+        points => Err(format!("expected 1 point, found {}", points.len())),
+    }
+}
+
+// Rectangle given on the command line as `x1,y1,x2,y2` by two opposite corners
+pub fn parse_rect(s: &str) -> Result<Rect, String> {
+    let fields: Vec<&str> = s.split(',').collect();
+    if fields.len() != 4 {
+        return Err(format!("expected 4 coordinates, found {}", fields.len()));
+    }
+    let coords = fields
+        .iter()
+        .map(|f| parse_coord(f, 1))
+        .collect::<Result<Vec<f32>, String>>()?;
+    let (a, b) = (
+        Point::new(coords[0], coords[1]),
+        Point::new(coords[2], coords[3]),
+    );
+    check_bounds(&a)?;
+    check_bounds(&b)?;
+    Ok(Rect::new(a.min(b), a.max(b)))
+}
+
+// Keeps the order of points as polygon vertices, makes it counterclockwise
+pub fn to_polygon(mut points: Vec<Point>) -> Result<Vec<Point>, String> {
+    let n = points.len();
+    let edge = |i: usize| Segment::new(points[i], points[(i + 1) % n]);
+    for i in 0..n {
+        // adjacent edges only share their vertex unless the boundary goes back along itself
+        let (prev, cur, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        if rotation(&prev, &cur, &next) == 0. && (prev - cur).dot(next - cur) > 0. {
+            return Err(format!(
+                "polygon edges {}-{} and {}-{} overlap",
+                (i + n - 1) % n + 1,
+                i + 1,
+                i + 1,
+                (i + 1) % n + 1
+            ));
+        }
+    }
+    for i in 0..n {
+        for j in i + 2..n {
+            if (j + 1) % n == i {
+                continue;
+            }
+            if intersect_seg(&edge(i), &edge(j)) {
+                return Err(format!(
+                    "polygon edges {}-{} and {}-{} intersect",
+                    i + 1,
+                    (i + 1) % n + 1,
+                    j + 1,
+                    (j + 1) % n + 1
+                ));
+            }
+        }
+    }
+    if signed_area(&points) < 0. {
+        points.reverse();
+    }
+    Ok(points)
+}
+
+// Every two points make a segment, segments can't cross or touch each other
+pub fn to_segments(points: Vec<Point>) -> Result<Vec<Segment>, String> {
+    if points.len() % 2 != 0 {
+        return Err(format!(
+            "the number of points should be even, found {}",
+            points.len()
+        ));
+    }
+    let segments: Vec<Segment> = points
+        .chunks_exact(2)
+        .map(|c| Segment::new(c[0], c[1]))
+        .collect();
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            if intersect_seg(&segments[i], &segments[j]) {
+                return Err(format!("segments {} and {} intersect", i + 1, j + 1));
+            }
+        }
+    }
+    Ok(segments)
+}
+
+// Directed lines given by two points as `x1 y1 x2 y2`, one per line, or as a JSON array of
+// `[x1, y1, x2, y2]` or `{"x1": .., "y1": .., "x2": .., "y2": ..}`
+pub fn parse_lines(content: &str) -> Result<Vec<Line>, String> {
+    let rows = if content.trim_start().starts_with('[') {
+        parse_json_rows(content, &["x1", "y1", "x2", "y2"], "lines")?
+    } else {
+        parse_rows(content, 4)?
+    };
+    let mut res = Vec::new();
+    for (line, c) in rows {
+        let (a, b) = (Point::new(c[0], c[1]), Point::new(c[2], c[3]));
+        check_bounds(&a)
+            .and(check_bounds(&b))
+            .map_err(|e| format!("line {}: {}", line, e))?;
+        if a == b {
+            return Err(format!(
+                "line {}: the line through ({}, {}) needs two different points",
+                line, a.x, a.y
+            ));
+        }
+        res.push(Line::new(a, b));
+    }
+    if res.len() < 3 {
+        return Err(String::from("the number of lines should be at least 3"));
+    }
+    Ok(res)
+}
+
+fn read_content(path: &str) -> Result<String, String> {
+    let mut content = String::new();
+    if path == "-" {
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| format!("failed to read stdin: {}", e))?;
+    } else {
+        content =
+            std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    }
+    Ok(content)
+}
+
+pub fn read_points(path: &str) -> Result<Vec<Point>, String> {
+    parse_points(&read_content(path)?)
+}
+
+pub fn read_lines(path: &str) -> Result<Vec<Line>, String> {
+    parse_lines(&read_content(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> Vec<Point> {
+        vec![
+            Point::new(1., 2.),
+            Point::new(3., 4.5),
+            Point::new(5., 6.),
+            Point::new(7., 1.),
+        ]
+    }
+
+    #[test]
+    fn parses_whitespace_separated() {
+        let content = "# lecture 3\n1 2\n3\t4.5\n\n5 6 # top\n7 1\n";
+        assert_eq!(Ok(expected()), parse_points(content));
+    }
+
+    #[test]
+    fn parses_csv_with_header() {
+        let content = "x,y\n1,2\n3, 4.5\n5,6\n7,1\n";
+        assert_eq!(Ok(expected()), parse_points(content));
+    }
+
+    #[test]
+    fn parses_json() {
+        let content = "[[1, 2], {\"x\": 3, \"y\": 4.5},\n [5, 6], [7, 1]]";
+        assert_eq!(Ok(expected()), parse_points(content));
+    }
+
+    #[test]
+    fn checks_polygons() {
+        let polygon = to_polygon(expected()).unwrap();
+        assert!(signed_area(&polygon) > 0.);
+        let mut bowtie = expected();
+        bowtie.swap(0, 1);
+        assert_eq!(
+            Err(String::from("polygon edges 2-3 and 4-1 intersect")),
+            to_polygon(bowtie)
+        );
+        let spike = vec![Point::new(1., 1.), Point::new(5., 5.), Point::new(3., 3.)];
+        assert_eq!(
+            Err(String::from("polygon edges 3-1 and 1-2 overlap")),
+            to_polygon(spike)
+        );
+    }
+
+    #[test]
+    fn checks_segments() {
+        assert_eq!(2, to_segments(expected()).unwrap().len());
+        assert_eq!(
+            Err(String::from("the number of points should be even, found 3")),
+            to_segments(expected()[..3].to_vec())
+        );
+        let mut crossing = expected();
+        crossing.swap(1, 2);
+        assert_eq!(
+            Err(String::from("segments 1 and 2 intersect")),
+            to_segments(crossing)
+        );
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        assert_eq!(
+            Err(String::from("line 3: expected 2 coordinates, found 3")),
+            parse_points("1 2\n3 4\n5 6 7\n8 9\n")
+        );
+        assert_eq!(
+            Err(String::from("line 4: point (1, 2) duplicates line 1")),
+            parse_points("1 2\n3 4\n5 6\n1 2\n")
+        );
+        assert_eq!(
+            Err(String::from("line 3: expected a number")),
+            parse_points("[[1, 2],\n [3, 4],\n [5, \"6\"],\n [7, 8]]")
+        );
+    }
+
+    #[test]
+    fn parses_lines() {
+        let content = "# triangle\n0 0 1 0\n1,0,0,1\n0 1 0 0\n";
+        let lines = parse_lines(content).unwrap();
+        assert_eq!(3, lines.len());
+        assert_eq!(Line::new(Point::new(1., 0.), Point::new(0., 1.)), lines[1]);
+        assert_eq!(
+            Err(String::from("line 2: expected 4 coordinates, found 2")),
+            parse_lines("0 0 1 0\n1 1\n")
+        );
+        assert_eq!(
+            Err(String::from(
+                "line 1: the line through (1, 1) needs two different points"
+            )),
+            parse_lines("1 1 1 1\n")
+        );
+        let json = "[[0, 0, 1, 0],\n {\"x1\": 1, \"y1\": 0, \"x2\": 0, \"y2\": 1},\n [0, 1, 0, 0]]";
+        assert_eq!(Ok(lines), parse_lines(json));
+        assert_eq!(
+            Err(String::from(
+                "line 2: expected [x1, y1, x2, y2] or {\"x1\": .., \"y1\": .., \"x2\": .., \"y2\": ..}"
+            )),
+            parse_lines("[[0, 0, 1, 0],\n [1, 0]]")
+        );
+    }
+
+    #[test]
+    fn parses_query_arguments() {
+        assert_eq!(Ok(Point::new(1.5, 2.)), parse_point("1.5,2"));
+        assert_eq!(
+            Err(String::from(
+                "point (20, 1) is outside of [0, 15] x [0, 15]"
+            )),
+            parse_point("20,1")
+        );
+        assert_eq!(
+            Err(String::from("line 1: expected 2 coordinates, found 3")),
+            parse_point("1,2,3")
+        );
+        assert_eq!(
+            Ok((Point::new(1., 2.), Point::new(3., 4.))),
+            parse_rect("3,2,1,4").map(|r| (r.lb, r.rt))
+        );
+        assert_eq!(
+            Err(String::from("line 1: coordinate 'nan' is not finite")),
+            parse_rect("nan,1,2,3").map(|r| (r.lb, r.rt))
+        );
+    }
+}
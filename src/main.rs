@@ -1,38 +1,83 @@
 mod algos;
+mod canvas;
 mod common;
 mod draw_context;
+mod export;
+mod generators;
+mod input;
+mod svg;
 mod ui;
 
+use crate::algos::bentley_ottmann::BentleyOttmann;
+use crate::algos::calipers_diameter::CalipersDiameter;
+use crate::algos::calipers_rectangle::CalipersRectangle;
+use crate::algos::calipers_width::CalipersWidth;
+use crate::algos::chan::Chan;
 use crate::algos::closest_pair_dnc::ClosestPairDivideAndConquer;
 use crate::algos::closest_pair_sl::ClosestPairSweepLine;
 use crate::algos::convex_hull_dnc::ConvexHullDivideAndConquer;
+use crate::algos::delaunay_bw::DelaunayBowyerWatson;
+use crate::algos::ear_clipping::EarClipping;
 use crate::algos::graham::Graham;
 use crate::algos::graham_andrew::GrahamAndrew;
+use crate::algos::half_plane::{tangent_lines, HalfPlaneIntersection};
+use crate::algos::incremental_hull::IncrementalHull;
+use crate::algos::jarvis::Jarvis;
+use crate::algos::kd_tree::KdTree;
+use crate::algos::kirkpatrick_seidel::KirkpatrickSeidel;
+use crate::algos::monotone_partition::MonotonePartition;
+use crate::algos::monotone_triangulation::MonotoneTriangulation;
+use crate::algos::quadtree::{self, Quadtree};
+use crate::algos::quickhull::Quickhull;
+use crate::algos::range_tree::{RangeTree, RangeTreeCascading};
 use crate::algos::shamos_hoey::ShamosHoey;
-use crate::algos::{all_states, Algo};
-use crate::common::*;
-use crate::ui::show;
+use crate::algos::trapezoidal_map::{random_segments, TrapezoidalMap};
+use crate::algos::voronoi_fortune::VoronoiFortune;
+use crate::algos::welzl::Welzl;
+use crate::algos::{Algo, WithParams};
+use crate::export::{export_frames, export_gif, export_svg, GifOptions};
+use crate::generators::*;
+use crate::input::{parse_point, parse_rect, read_lines, read_points, to_polygon, to_segments};
+use crate::ui::{show, Editable, ShowOptions};
 use clap::{value_t, App, Arg};
 use rand::rngs::{OsRng, StdRng};
-use rand::{Rng, RngCore, SeedableRng};
+use rand::{RngCore, SeedableRng};
 
-fn random_points(n: usize, mut rng: impl Rng) -> Vec<Point> {
-    let mut res = Vec::with_capacity(n);
-    for _ in 0..n {
-        res.push(Point::new(
-            rng.gen_range(0., MAX_X),
-            rng.gen_range(0., MAX_Y),
-        ));
-    }
-    res
+const POLYGON_ALGOS: &[&str] = &[
+    "ear_clipping",
+    "monotone_partition",
+    "monotone_triangulation",
+];
+
+enum Output {
+    Window(ShowOptions),
+    Gif(String, GifOptions),
+    Frames(String, usize, f32),
+    Svg(String, Option<usize>, usize, f32),
 }
 
-fn run<TAlgo>(points: Vec<Point>, window_size: usize, draw_width: f32)
+fn run<TAlgo>(input: TAlgo::Input, output: &Output)
 where
     TAlgo: Algo,
+    TAlgo::Input: Editable,
 {
-    let (states, actions) = all_states::<TAlgo>(points);
-    show::<TAlgo>(&states, &actions, window_size, draw_width);
+    let result = match output {
+        Output::Window(options) => {
+            show::<TAlgo>(input, options);
+            Ok(())
+        }
+        Output::Gif(path, options) => export_gif::<TAlgo>(input, path, options),
+        Output::Frames(dir, size, draw_width) => {
+            export_frames::<TAlgo>(input, dir, *size, *draw_width)
+        }
+        Output::Svg(path, step, size, draw_width) => {
+            export_svg::<TAlgo>(input, path, *step, *size, *draw_width)
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to export: {}", e);
+        std::process::exit(1);
+    }
 }
 
 fn main() {
@@ -45,12 +90,33 @@ fn main() {
                 .long("algorithm")
                 .takes_value(true)
                 .possible_values(&[
+                    "bentley_ottmann",
+                    "calipers_diameter",
+                    "calipers_rectangle",
+                    "calipers_width",
+                    "chan",
                     "closest_pair_dnc",
                     "closest_pair_sl",
                     "convex_hull_dnc",
+                    "delaunay_bw",
+                    "ear_clipping",
                     "graham",
                     "graham_andrew",
+                    "half_plane",
+                    "incremental_hull",
+                    "jarvis",
+                    "kd_tree",
+                    "kirkpatrick_seidel",
+                    "monotone_partition",
+                    "monotone_triangulation",
+                    "quadtree",
+                    "quickhull",
+                    "range_tree",
+                    "range_tree_fc",
                     "shamos_hoey",
+                    "trapezoidal_map",
+                    "voronoi_fortune",
+                    "welzl",
                 ])
                 .required(true)
                 .index(1),
@@ -69,6 +135,62 @@ fn main() {
                 .default_value("50")
                 .validator(is_number),
         )
+        .arg(
+            Arg::with_name("distribution")
+                .long("distribution")
+                .short("D")
+                .takes_value(true)
+                .possible_values(&["uniform", "circle", "gaussian", "grid", "bands", "parabola"])
+                .default_value("uniform"),
+        )
+        .arg(
+            Arg::with_name("clusters")
+                .long("clusters")
+                .takes_value(true)
+                .default_value("3")
+                .validator(is_positive)
+                .help("Number of clusters for gaussian and bands distributions"),
+        )
+        .arg(
+            Arg::with_name("sigma")
+                .long("sigma")
+                .takes_value(true)
+                .validator(is_positive_float)
+                .help("Standard deviation for gaussian [default: 1] and bands [default: 0.05] distributions"),
+        )
+        .arg(
+            Arg::with_name("input")
+                .long("input")
+                .short("i")
+                .takes_value(true)
+                .value_name("file")
+                .help("Reads points (lines for half_plane) from CSV, whitespace-separated or JSON file ('-' for stdin)"),
+        )
+        .arg(
+            Arg::with_name("query")
+                .long("query")
+                .short("q")
+                .takes_value(true)
+                .value_name("x,y")
+                .validator(|val| parse_point(&val).map(|_| ()))
+                .help("Query point for kd_tree and trapezoidal_map, chosen randomly from the seed by default"),
+        )
+        .arg(
+            Arg::with_name("range")
+                .long("range")
+                .takes_value(true)
+                .value_name("x1,y1,x2,y2")
+                .validator(|val| parse_rect(&val).map(|_| ()))
+                .help("Query rectangle for quadtree and range_tree, chosen randomly from the seed by default"),
+        )
+        .arg(
+            Arg::with_name("capacity")
+                .long("capacity")
+                .takes_value(true)
+                .default_value("1")
+                .validator(is_positive)
+                .help("Maximum number of points in a quadtree cell"),
+        )
         .arg(
             Arg::with_name("window size")
                 .long("window_size")
@@ -83,23 +205,202 @@ fn main() {
                 .takes_value(true)
                 .default_value("0.1"),
         )
+        .arg(
+            Arg::with_name("autoplay")
+                .long("autoplay")
+                .short("a")
+                .help("Starts playing steps automatically (Space toggles, Up/Down change speed)"),
+        )
+        .arg(
+            Arg::with_name("loop")
+                .long("loop")
+                .short("l")
+                .help("Restarts playing from the first step after the last one (L toggles)"),
+        )
+        .arg(
+            Arg::with_name("export gif")
+                .long("export-gif")
+                .takes_value(true)
+                .value_name("path")
+                .help("Renders all steps into an animated GIF instead of opening a window"),
+        )
+        .arg(
+            Arg::with_name("export frames")
+                .long("export-frames")
+                .takes_value(true)
+                .value_name("dir")
+                .conflicts_with("export gif")
+                .help("Renders every step into numbered PNG files instead of opening a window"),
+        )
+        .arg(
+            Arg::with_name("export svg")
+                .long("export-svg")
+                .takes_value(true)
+                .value_name("path")
+                .conflicts_with_all(&["export gif", "export frames"])
+                .help("Writes the --step as an SVG file, or every step into numbered SVG files in the directory"),
+        )
+        .arg(
+            Arg::with_name("step")
+                .long("step")
+                .takes_value(true)
+                .value_name("N")
+                .requires("export svg")
+                .help("Step index to export, as numbered by --export-frames"),
+        )
+        .arg(
+            Arg::with_name("delay")
+                .long("delay")
+                .takes_value(true)
+                .value_name("ms")
+                .default_value("500")
+                .help("Delay between exported GIF frames"),
+        )
+        .arg(
+            Arg::with_name("hold")
+                .long("hold")
+                .takes_value(true)
+                .value_name("frames")
+                .default_value("0")
+                .help("Number of extra GIF frames showing the final state"),
+        )
         .get_matches();
 
+    let algo = matches.value_of("algo").unwrap();
+    // Polygon algorithms keep the order of the input points and generate a random simple polygon
+    let polygon = POLYGON_ALGOS.contains(&algo);
     let n = value_t!(matches, "number", usize).unwrap();
     let seed = value_t!(matches, "seed", u64).unwrap_or_else(|_| OsRng.next_u64());
     let window_size = value_t!(matches, "window size", usize).unwrap();
     let draw_width = value_t!(matches, "draw width", f32).unwrap();
+    let output = if let Some(path) = matches.value_of("export svg") {
+        let step = matches
+            .value_of("step")
+            .map(|_| value_t!(matches, "step", usize).unwrap_or_else(|e| e.exit()));
+        Output::Svg(path.to_string(), step, window_size, draw_width)
+    } else if let Some(dir) = matches.value_of("export frames") {
+        Output::Frames(dir.to_string(), window_size, draw_width)
+    } else if let Some(path) = matches.value_of("export gif") {
+        Output::Gif(
+            path.to_string(),
+            GifOptions {
+                size: window_size,
+                draw_width,
+                delay: value_t!(matches, "delay", u32).unwrap_or_else(|e| e.exit()),
+                hold_frames: value_t!(matches, "hold", usize).unwrap_or_else(|e| e.exit()),
+            },
+        )
+    } else {
+        Output::Window(ShowOptions {
+            window_size,
+            draw_width,
+            autoplay: matches.is_present("autoplay"),
+            looped: matches.is_present("loop"),
+            polygon,
+        })
+    };
+    let clusters = value_t!(matches, "clusters", usize).unwrap_or_else(|e| e.exit());
+    let sigma = matches
+        .value_of("sigma")
+        .map(|_| value_t!(matches, "sigma", f32).unwrap_or_else(|e| e.exit()));
+
+    let query = matches
+        .value_of("query")
+        .map_or_else(|| random_point(seed), |val| parse_point(val).unwrap());
+    let range = matches
+        .value_of("range")
+        .map_or_else(|| random_rect(seed), |val| parse_rect(val).unwrap());
+    let capacity = value_t!(matches, "capacity", usize).unwrap_or_else(|e| e.exit());
 
-    println!("Seed: {}", seed);
-    let points = random_points(n, StdRng::seed_from_u64(seed));
+    let half_plane = algo == "half_plane";
+    let points = match matches.value_of("input") {
+        // lines are read instead of points
+        Some(_) if half_plane => Vec::new(),
+        Some(path) => read_points(path)
+            .and_then(|points| {
+                if polygon {
+                    to_polygon(points)
+                } else {
+                    Ok(points)
+                }
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to read points: {}", e);
+                std::process::exit(1);
+            }),
+        None => {
+            println!("Seed: {}", seed);
+            let distribution: Box<dyn Distribution> = match matches.value_of("distribution") {
+                _ if polygon => Box::new(SimplePolygon),
+                Some("circle") => Box::new(Circle),
+                Some("gaussian") => Box::new(Gaussian {
+                    clusters,
+                    sigma: sigma.unwrap_or(1.),
+                }),
+                Some("grid") => Box::new(Grid),
+                Some("bands") => Box::new(Bands {
+                    bands: clusters,
+                    sigma: sigma.unwrap_or(0.05),
+                }),
+                Some("parabola") => Box::new(Parabola),
+                _ => Box::new(Uniform),
+            };
+            distribution.generate(n, &mut StdRng::seed_from_u64(seed))
+        }
+    };
 
-    match matches.value_of("algo").unwrap() {
-        "closest_pair_dnc" => run::<ClosestPairDivideAndConquer>(points, window_size, draw_width),
-        "closest_pair_sl" => run::<ClosestPairSweepLine>(points, window_size, draw_width),
-        "convex_hull_dnc" => run::<ConvexHullDivideAndConquer>(points, window_size, draw_width),
-        "graham_andrew" => run::<GrahamAndrew>(points, window_size, draw_width),
-        "graham" => run::<Graham>(points, window_size, draw_width),
-        "shamos_hoey" => run::<ShamosHoey>(points, window_size, draw_width),
+    match algo {
+        "bentley_ottmann" => run::<BentleyOttmann>(points, &output),
+        "calipers_diameter" => run::<CalipersDiameter>(points, &output),
+        "calipers_rectangle" => run::<CalipersRectangle>(points, &output),
+        "calipers_width" => run::<CalipersWidth>(points, &output),
+        "chan" => run::<Chan>(points, &output),
+        "closest_pair_dnc" => run::<ClosestPairDivideAndConquer>(points, &output),
+        "closest_pair_sl" => run::<ClosestPairSweepLine>(points, &output),
+        "convex_hull_dnc" => run::<ConvexHullDivideAndConquer>(points, &output),
+        "delaunay_bw" => run::<DelaunayBowyerWatson>(points, &output),
+        "ear_clipping" => run::<EarClipping>(points, &output),
+        "graham_andrew" => run::<GrahamAndrew>(points, &output),
+        "graham" => run::<Graham>(points, &output),
+        "half_plane" => {
+            let lines = match matches.value_of("input") {
+                Some(path) => read_lines(path).unwrap_or_else(|e| {
+                    eprintln!("Failed to read lines: {}", e);
+                    std::process::exit(1);
+                }),
+                None => tangent_lines(points),
+            };
+            run::<HalfPlaneIntersection>(lines, &output)
+        }
+        "incremental_hull" => run::<IncrementalHull>(insertion_order(points, seed), &output),
+        "jarvis" => run::<Jarvis>(points, &output),
+        "kd_tree" => run::<KdTree>(WithParams::new(points, query), &output),
+        "kirkpatrick_seidel" => run::<KirkpatrickSeidel>(points, &output),
+        "monotone_partition" => run::<MonotonePartition>(points, &output),
+        "monotone_triangulation" => run::<MonotoneTriangulation>(points, &output),
+        "quadtree" => {
+            let params = quadtree::Params { range, capacity };
+            run::<Quadtree>(WithParams::new(points, params), &output)
+        }
+        "quickhull" => run::<Quickhull>(points, &output),
+        "range_tree" => run::<RangeTree>(WithParams::new(points, range), &output),
+        "range_tree_fc" => run::<RangeTreeCascading>(WithParams::new(points, range), &output),
+        "shamos_hoey" => run::<ShamosHoey>(points, &output),
+        "trapezoidal_map" => {
+            // input points are taken in pairs
+            let segments = if matches.is_present("input") {
+                to_segments(points).unwrap_or_else(|e| {
+                    eprintln!("Failed to read segments: {}", e);
+                    std::process::exit(1);
+                })
+            } else {
+                random_segments(points)
+            };
+            let segments = insertion_order(segments, seed);
+            run::<TrapezoidalMap>(WithParams::new(segments, query), &output)
+        }
+        "voronoi_fortune" => run::<VoronoiFortune>(points, &output),
+        "welzl" => run::<Welzl>(insertion_order(points, seed), &output),
         _ => panic!(),
     }
 }
@@ -116,3 +417,19 @@ fn is_number(val: String) -> Result<(), String> {
         Ok(())
     }
 }
+
+fn is_positive(val: String) -> Result<(), String> {
+    match val.parse::<usize>() {
+        Ok(0) => Err(String::from("the value should be positive")),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("failed to parse a number: {}", e)),
+    }
+}
+
+fn is_positive_float(val: String) -> Result<(), String> {
+    match val.parse::<f32>() {
+        Ok(v) if v.is_finite() && v > 0. => Ok(()),
+        Ok(_) => Err(String::from("the value should be finite and positive")),
+        Err(e) => Err(format!("failed to parse a number: {}", e)),
+    }
+}
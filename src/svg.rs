@@ -0,0 +1,153 @@
+use crate::canvas::*;
+use crate::common::*;
+use raqote::{SolidSource, Transform};
+use std::fmt::Write;
+
+fn color(c: &SolidSource) -> String {
+    format!("rgb({},{},{})", c.r, c.g, c.b)
+}
+
+fn opacity(c: &SolidSource) -> f32 {
+    f32::from(c.a) / 255.
+}
+
+// Elements are kept in world coordinates, the transform is applied by the enclosing group
+pub struct SvgCanvas {
+    size: (usize, usize),
+    transform: Transform,
+    draw_width: f32,
+    elements: Vec<String>,
+}
+
+impl SvgCanvas {
+    pub fn new(size: (usize, usize), draw_width: f32) -> Self {
+        Self {
+            size,
+            transform: world_transform(size),
+            draw_width,
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let t = &self.transform;
+        let mut res = String::new();
+        writeln!(
+            res,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.size.0,
+            h = self.size.1
+        )
+        .unwrap();
+        writeln!(res, r#"<rect width="100%" height="100%" fill="black"/>"#).unwrap();
+        writeln!(
+            res,
+            r#"<g transform="matrix({} {} {} {} {} {})">"#,
+            t.m11, t.m12, t.m21, t.m22, t.m31, t.m32
+        )
+        .unwrap();
+        for element in &self.elements {
+            writeln!(res, "{}", element).unwrap();
+        }
+        res.push_str("</g>\n</svg>\n");
+        res
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn draw_width(&self) -> f32 {
+        self.draw_width
+    }
+
+    fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    fn draw_point(&mut self, a: &Point, c: SolidSource) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}" fill-opacity="{}"/>"#,
+            a.x,
+            a.y,
+            self.draw_width,
+            color(&c),
+            opacity(&c)
+        ));
+    }
+
+    fn draw_polyline(&mut self, points: &[Point], c: SolidSource) {
+        let mut coords = String::new();
+        for p in points {
+            write!(coords, "{},{} ", p.x, p.y).unwrap();
+        }
+        self.elements.push(format!(
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="miter"/>"#,
+            coords.trim_end(),
+            color(&c),
+            opacity(&c),
+            self.draw_width / 1.5
+        ));
+    }
+
+    fn draw_circle(&mut self, center: &Point, r: f32, c: SolidSource) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}"/>"#,
+            center.x,
+            center.y,
+            r,
+            color(&c),
+            opacity(&c),
+            self.draw_width / 1.5
+        ));
+    }
+
+    fn fill_circle(&mut self, center: &Point, r: f32, c: SolidSource) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}" fill-opacity="{}"/>"#,
+            center.x,
+            center.y,
+            r,
+            color(&c),
+            opacity(&c) * 0.3
+        ));
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], c: SolidSource) {
+        let mut coords = String::new();
+        for p in points {
+            write!(coords, "{},{} ", p.x, p.y).unwrap();
+        }
+        self.elements.push(format!(
+            r#"<polygon points="{}" fill="{}" fill-opacity="{}"/>"#,
+            coords.trim_end(),
+            color(&c),
+            opacity(&c) * 0.3
+        ));
+    }
+
+    fn draw_quadratic(&mut self, a: &Point, control: &Point, b: &Point, c: SolidSource) {
+        self.elements.push(format!(
+            r#"<path d="M {},{} Q {},{} {},{}" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+            a.x,
+            a.y,
+            control.x,
+            control.y,
+            b.x,
+            b.y,
+            color(&c),
+            opacity(&c),
+            self.draw_width / 1.5
+        ));
+    }
+
+    fn fill_rect(&mut self, lb: &Point, rt: &Point, c: SolidSource) {
+        self.elements.push(format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="{}"/>"#,
+            lb.x - self.draw_width / 2.0,
+            lb.y - self.draw_width / 2.0,
+            rt.x - lb.x + self.draw_width,
+            rt.y - lb.y + self.draw_width,
+            color(&c),
+            opacity(&c) * 0.3
+        ));
+    }
+}
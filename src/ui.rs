@@ -1,13 +1,32 @@
-use crate::algos::Algo;
+use crate::algos::{all_states, draw_step, Algo, WithParams};
+use crate::canvas::Canvas;
+use crate::common::*;
 use crate::draw_context::DrawContext;
-use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use crate::input::to_polygon;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+
+// Algorithms expect the same minimal number of points as the command line accepts
+const MIN_POINTS: usize = 4;
+const MIN_LINES: usize = 3;
+// Length of a segment added by a click, its other end is dragged right away
+const NEW_SEGMENT: f32 = 0.1;
+const MAX_FRAMES_PER_STEP: u32 = 120;
+
+pub struct ShowOptions {
+    pub window_size: usize,
+    pub draw_width: f32,
+    pub autoplay: bool,
+    pub looped: bool,
+    // edits keep the points a simple polygon
+    pub polygon: bool,
+}
 
 fn get_next_index(window: &Window, index: usize, max_index: usize) -> usize {
     if window.is_key_pressed(Key::Right, KeyRepeat::Yes) {
         std::cmp::min(index + 1, max_index)
     } else if window.is_key_pressed(Key::Left, KeyRepeat::Yes) {
         index.saturating_sub(1)
-    } else if window.is_key_pressed(Key::Home, KeyRepeat::No) || index == std::usize::MAX {
+    } else if window.is_key_pressed(Key::Home, KeyRepeat::No) || index == usize::MAX {
         0
     } else if window.is_key_pressed(Key::End, KeyRepeat::No) {
         max_index
@@ -16,36 +35,325 @@ fn get_next_index(window: &Window, index: usize, max_index: usize) -> usize {
     }
 }
 
-pub fn show<TAlgo>(
-    states: &[TAlgo::State],
-    actions: &[TAlgo::Action],
-    window_size: usize,
-    draw_width: f32,
-) where
+// Steps forward every `frames_per_step` window updates, so the speed follows the ~60 fps loop
+struct Player {
+    playing: bool,
+    looped: bool,
+    frames_per_step: u32,
+    frame: u32,
+}
+
+impl Player {
+    fn new(options: &ShowOptions) -> Self {
+        Self {
+            playing: options.autoplay,
+            looped: options.looped,
+            frames_per_step: 30,
+            frame: 0,
+        }
+    }
+
+    fn update(&mut self, window: &Window, index: usize, max_index: usize) -> usize {
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            self.playing = !self.playing;
+            self.frame = 0;
+            if self.playing && index == max_index {
+                return 0;
+            }
+        }
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            self.looped = !self.looped;
+        }
+        if window.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+            self.frames_per_step = std::cmp::max(self.frames_per_step / 2, 1);
+        } else if window.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            self.frames_per_step = std::cmp::min(self.frames_per_step * 2, MAX_FRAMES_PER_STEP);
+        }
+        if !self.playing || index == usize::MAX {
+            return index;
+        }
+        self.frame += 1;
+        if self.frame < self.frames_per_step {
+            return index;
+        }
+        self.frame = 0;
+        if index < max_index {
+            index + 1
+        } else if self.looped {
+            0
+        } else {
+            self.playing = false;
+            index
+        }
+    }
+}
+
+fn find_point(points: &[Point], p: &Point, radius: f32) -> Option<usize> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i, (*a - *p).square_length()))
+        .filter(|(_, d)| *d <= radius * radius)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(i, _)| i)
+}
+
+// Input edited in the window by dragging, adding and removing its handle points
+pub trait Editable {
+    fn handles(&self) -> Vec<Point>;
+    // Returns the new index of the handle, which can change as it moves
+    fn move_handle(&mut self, i: usize, p: Point) -> usize;
+    // Returns the handle to drag after adding
+    fn add_handle(&mut self, p: Point) -> usize;
+    // Returns false if the input can't get smaller
+    fn remove_handle(&mut self, i: usize) -> bool;
+    // Edits leading to an invalid input are undone
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+impl Editable for Vec<Point> {
+    fn handles(&self) -> Vec<Point> {
+        self.clone()
+    }
+
+    fn move_handle(&mut self, i: usize, p: Point) -> usize {
+        self[i] = p;
+        i
+    }
+
+    fn add_handle(&mut self, p: Point) -> usize {
+        self.push(p);
+        self.len() - 1
+    }
+
+    fn remove_handle(&mut self, i: usize) -> bool {
+        if self.len() <= MIN_POINTS {
+            return false;
+        }
+        self.remove(i);
+        true
+    }
+}
+
+// Handles are the ends of the segments
+impl Editable for Vec<Segment> {
+    fn handles(&self) -> Vec<Point> {
+        self.iter().flat_map(|s| vec![s.a, s.b]).collect()
+    }
+
+    fn move_handle(&mut self, i: usize, p: Point) -> usize {
+        let s = &mut self[i / 2];
+        let other = if i % 2 == 0 { s.b } else { s.a };
+        *s = Segment::new(p, other);
+        if s.a == p {
+            i / 2 * 2
+        } else {
+            i / 2 * 2 + 1
+        }
+    }
+
+    fn add_handle(&mut self, p: Point) -> usize {
+        let dx = if p.x + NEW_SEGMENT <= MAX_X {
+            NEW_SEGMENT
+        } else {
+            -NEW_SEGMENT
+        };
+        self.push(Segment::new(p, p));
+        self.move_handle(self.len() * 2 - 1, Point::new(p.x + dx, p.y))
+    }
+
+    fn remove_handle(&mut self, i: usize) -> bool {
+        if self.len() * 2 <= MIN_POINTS {
+            return false;
+        }
+        self.remove(i / 2);
+        true
+    }
+
+    fn is_valid(&self) -> bool {
+        (0..self.len()).all(|i| {
+            self[i].a != self[i].b
+                && (i + 1..self.len()).all(|j| !intersect_seg(&self[i], &self[j]))
+        })
+    }
+}
+
+// Handles are the two points of each line, a new line faces the center of the area
+impl Editable for Vec<Line> {
+    fn handles(&self) -> Vec<Point> {
+        self.iter().flat_map(|l| vec![l.a, l.b]).collect()
+    }
+
+    fn move_handle(&mut self, i: usize, p: Point) -> usize {
+        let l = &mut self[i / 2];
+        if i % 2 == 0 {
+            l.a = p;
+        } else {
+            l.b = p;
+        }
+        i
+    }
+
+    fn add_handle(&mut self, p: Point) -> usize {
+        self.push(Line::facing_center(p));
+        // dragging the second point turns the line
+        self.len() * 2 - 1
+    }
+
+    fn remove_handle(&mut self, i: usize) -> bool {
+        if self.len() <= MIN_LINES {
+            return false;
+        }
+        self.remove(i / 2);
+        true
+    }
+
+    fn is_valid(&self) -> bool {
+        self.iter().all(|l| l.a != l.b)
+    }
+}
+
+// Parameters aren't edited
+impl<T: Editable, P> Editable for WithParams<T, P> {
+    fn handles(&self) -> Vec<Point> {
+        self.data.handles()
+    }
+
+    fn move_handle(&mut self, i: usize, p: Point) -> usize {
+        self.data.move_handle(i, p)
+    }
+
+    fn add_handle(&mut self, p: Point) -> usize {
+        self.data.add_handle(p)
+    }
+
+    fn remove_handle(&mut self, i: usize) -> bool {
+        self.data.remove_handle(i)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.data.is_valid()
+    }
+}
+
+#[derive(Default)]
+struct PointEditor {
+    dragged: Option<usize>,
+    left_down: bool,
+    right_down: bool,
+    polygon: bool,
+}
+
+impl PointEditor {
+    // Left click adds a point or starts dragging an existing one, right click deletes a point.
+    // Returns true if the input was changed, edits making the input invalid or the polygon
+    // self-intersecting are undone.
+    fn update<T>(&mut self, window: &Window, dc: &DrawContext, input: &mut T) -> bool
+    where
+        T: Editable + Clone,
+    {
+        let (before, dragged) = (input.clone(), self.dragged);
+        if !self.edit(window, dc, input) {
+            return false;
+        }
+        if !input.is_valid() || (self.polygon && to_polygon(input.handles()).is_err()) {
+            *input = before;
+            self.dragged = dragged;
+            return false;
+        }
+        true
+    }
+
+    fn edit<T: Editable>(&mut self, window: &Window, dc: &DrawContext, input: &mut T) -> bool {
+        let left_down = window.get_mouse_down(MouseButton::Left);
+        let right_down = window.get_mouse_down(MouseButton::Right);
+        let left_pressed = left_down && !self.left_down;
+        let right_pressed = right_down && !self.right_down;
+        self.left_down = left_down;
+        self.right_down = right_down;
+        if !left_down {
+            self.dragged = None;
+        }
+
+        let pos = match window.get_mouse_pos(MouseMode::Clamp) {
+            Some((x, y)) => dc.to_world(x, y),
+            None => return false,
+        };
+        let pos = Point::new(pos.x.clamp(0., MAX_X), pos.y.clamp(0., MAX_Y));
+        let radius = dc.draw_width() * 3.;
+        let handles = input.handles();
+
+        if let Some(i) = self.dragged {
+            if handles.contains(&pos) {
+                return false;
+            }
+            self.dragged = Some(input.move_handle(i, pos));
+            true
+        } else if left_pressed {
+            match find_point(&handles, &pos, radius) {
+                Some(i) => {
+                    self.dragged = Some(i);
+                    false
+                }
+                None => {
+                    self.dragged = Some(input.add_handle(pos));
+                    true
+                }
+            }
+        } else if right_pressed {
+            match find_point(&handles, &pos, radius) {
+                Some(i) => input.remove_handle(i),
+                None => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+pub fn show<TAlgo>(mut input: TAlgo::Input, options: &ShowOptions)
+where
     TAlgo: Algo,
+    TAlgo::Input: Editable,
 {
     let title = "Geometry Algorithms Visualization";
-    let mut window =
-        Window::new(title, window_size, window_size, WindowOptions::default()).unwrap();
-    let mut index = std::usize::MAX;
+    let mut window = Window::new(
+        title,
+        options.window_size,
+        options.window_size,
+        WindowOptions::default(),
+    )
+    .unwrap();
+    let mut index = usize::MAX;
     let size = window.get_size();
-    let mut dc = DrawContext::new(size, draw_width);
+    let mut dc = DrawContext::new(size, options.draw_width);
+    let mut editor = PointEditor {
+        polygon: options.polygon,
+        ..PointEditor::default()
+    };
+    let mut player = Player::new(options);
+    let (mut states, mut actions) = all_states::<TAlgo>(input.clone());
 
     // Limit to max ~60 fps update rate
     window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
     window.set_key_repeat_rate(0.01);
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
-        let new_index = get_next_index(&window, index, actions.len() * 2);
-        if new_index != index {
-            index = new_index;
+        let edited = editor.update(&window, &dc, &mut input);
+        if edited {
+            let (new_states, new_actions) = all_states::<TAlgo>(input.clone());
+            states = new_states;
+            actions = new_actions;
+        }
+        let max_index = actions.len() * 2;
+        let new_index =
+            get_next_index(&window, player.update(&window, index, max_index), max_index);
+        if new_index != index || edited {
+            index = new_index.min(max_index);
             dc.clear();
-            if index % 2 == 0 {
-                TAlgo::draw_state(&mut dc, &states[index / 2]);
-            } else {
-                TAlgo::draw_state(&mut dc, &states[index / 2]);
-                TAlgo::draw_action(&mut dc, &actions[index / 2]);
-            }
+            draw_step::<TAlgo>(&mut dc, &states, &actions, index);
             window
                 .update_with_buffer(dc.get_data(), size.0, size.1)
                 .unwrap();